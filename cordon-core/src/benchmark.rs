@@ -0,0 +1,107 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::fmt::{self, Display, Formatter};
+
+use crate::{
+    bot::BotDifficulty,
+    common::Color,
+    game::{ArenaShape, ArenaSize, Controller, GameState, ROUND_TIME_LIMIT_TICKS},
+};
+
+/// The score that wins a game between the bots.
+const MAX_SCORE: u32 = 3;
+
+/// The results of a number of games between two bots, to compare and tune them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Benchmark {
+    /// The difficulty of both bots, in order.
+    pub bots: [BotDifficulty; 2],
+    pub games: usize,
+    /// The number of games that each bot won.
+    pub wins: [usize; 2],
+    pub draws: usize,
+    /// The number of moves in all games together.
+    pub moves: usize,
+}
+
+impl Benchmark {
+    /// The part of the games that the bot with the specified index won, from 0.0 to 1.0.
+    pub fn win_rate(&self, bot: usize) -> f64 {
+        self.wins[bot] as f64 / self.games.max(1) as f64
+    }
+
+    /// The average number of moves in a game.
+    pub fn average_moves(&self) -> f64 {
+        self.moves as f64 / self.games.max(1) as f64
+    }
+}
+
+impl Display for Benchmark {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} vs {}: {} games, {:.1}% - {:.1}%, {} draws, {:.1} moves per game",
+            self.bots[0],
+            self.bots[1],
+            self.games,
+            self.win_rate(0) * 100.0,
+            self.win_rate(1) * 100.0,
+            self.draws,
+            self.average_moves(),
+        )
+    }
+}
+
+/// Play `games` games between two bots without drawing them, as fast as possible. Game `i` is
+/// played with seed `seed + i`, so the same arguments always give the same results. The bots
+/// take turns being the first player, since moving first can be an advantage. Rounds have the
+/// time limit of [`ROUND_TIME_LIMIT_TICKS`], after which the bot with the most room wins them.
+pub fn self_play(bots: [BotDifficulty; 2], games: usize, seed: u64) -> Benchmark {
+    let (width, height) = ArenaSize::Medium.dimensions(ArenaShape::default());
+    let colors = [Color::red(), Color::blue()];
+    let mut benchmark = Benchmark {
+        bots,
+        games,
+        wins: [0, 0],
+        draws: 0,
+        moves: 0,
+    };
+
+    for i in 0..games {
+        // the bot with index `order[p]` plays as player `p`
+        let order = if i % 2 == 0 { [0, 1] } else { [1, 0] };
+        let mut game_state =
+            GameState::new(0, 2, bots[order[0]], MAX_SCORE, width, height, &colors);
+        game_state.players[1].controller = Controller::Bot(bots[order[1]]);
+        game_state.set_seed(seed.wrapping_add(i as u64));
+        game_state.max_round_ticks = Some(ROUND_TIME_LIMIT_TICKS);
+
+        // every game ends, at the latest after a number of drawn rounds in a row
+        game_state.tick_n(usize::MAX);
+
+        match game_state.winner() {
+            Some(winner) => benchmark.wins[order[winner]] += 1,
+            None => benchmark.draws += 1,
+        }
+        benchmark.moves += game_state.recording.moves.len();
+    }
+
+    benchmark
+}
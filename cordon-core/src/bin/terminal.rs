@@ -0,0 +1,284 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Play Cordon in a terminal, against a bot or watching two bots. The grid is drawn with ANSI
+//! colors, and the arrow keys or W, A, S and D steer. P pauses and Q quits.
+//!
+//! With `--benchmark`, the bot plays that many games against the bot of `--against` without
+//! drawing them, and the results are printed, to compare and tune the bots.
+//!
+//! Usage: `cordon-terminal [--watch] [--bot <difficulty>] [--seed <number>] [--benchmark <games> [--against <difficulty>]]`
+
+use std::{
+    io::{self, Read, Write},
+    process::Command,
+    sync::mpsc::{self, Receiver},
+    thread,
+    time::Duration,
+};
+
+use cordon_core::{
+    benchmark,
+    bot::BotDifficulty,
+    common::{Color, Direction},
+    game::{ArenaShape, ArenaSize, GameState, Phase},
+    layout::{Cell, Grid},
+};
+
+/// The arguments that the terminal front-end takes, shown when they are wrong.
+const USAGE: &str = "usage: cordon-terminal [--watch] [--bot <difficulty>] [--seed <number>] \
+                     [--benchmark <games> [--against <difficulty>]]";
+
+/// A key that was pressed, as far as the game is concerned.
+enum Key {
+    Steer(Direction),
+    Pause,
+    Quit,
+}
+
+struct Options {
+    /// Let two bots play, without a human player.
+    watch: bool,
+    bot_difficulty: BotDifficulty,
+    seed: Option<u64>,
+    /// The number of games to play between the bots as a benchmark, instead of playing in the
+    /// terminal.
+    benchmark: Option<usize>,
+    /// The difficulty of the other bot in a benchmark.
+    against: BotDifficulty,
+}
+
+fn parse_options() -> Result<Options, String> {
+    let mut options = Options {
+        watch: false,
+        bot_difficulty: BotDifficulty::Medium,
+        seed: None,
+        benchmark: None,
+        against: BotDifficulty::Medium,
+    };
+    let mut args = std::env::args().skip(1);
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--watch" => options.watch = true,
+            "--bot" => {
+                let name = args.next().ok_or("--bot needs a difficulty")?;
+                options.bot_difficulty = parse_difficulty(&name)?;
+            }
+            "--seed" => {
+                let seed = args.next().ok_or("--seed needs a number")?;
+                options.seed = Some(seed.parse().map_err(|_| format!("not a seed: {}", seed))?);
+            }
+            "--benchmark" => {
+                let games = args.next().ok_or("--benchmark needs a number of games")?;
+                let games = games.parse().map_err(|_| format!("not a number: {}", games))?;
+                options.benchmark = Some(games);
+            }
+            "--against" => {
+                let name = args.next().ok_or("--against needs a difficulty")?;
+                options.against = parse_difficulty(&name)?;
+            }
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+
+    Ok(options)
+}
+
+/// Find the difficulty with the specified name, like "hard", in any case.
+fn parse_difficulty(name: &str) -> Result<BotDifficulty, String> {
+    let mut difficulty = BotDifficulty::Easy;
+    loop {
+        if difficulty.to_string().eq_ignore_ascii_case(name) {
+            return Ok(difficulty);
+        }
+        difficulty = difficulty.next();
+        if difficulty == BotDifficulty::Easy {
+            return Err(format!("unknown bot difficulty: {}", name));
+        }
+    }
+}
+
+/// Read the keys from stdin on another thread, so the game goes on while nobody presses anything.
+fn read_keys() -> Receiver<Key> {
+    let (sender, receiver) = mpsc::channel();
+
+    thread::spawn(move || {
+        let mut bytes = io::stdin().lock().bytes().map_while(Result::ok);
+        while let Some(byte) = bytes.next() {
+            let key = match byte {
+                // the arrow keys send an escape sequence, like ESC [ A for up
+                0x1b => match (bytes.next(), bytes.next()) {
+                    (Some(b'['), Some(b'A')) => Key::Steer(Direction::North),
+                    (Some(b'['), Some(b'B')) => Key::Steer(Direction::South),
+                    (Some(b'['), Some(b'C')) => Key::Steer(Direction::East),
+                    (Some(b'['), Some(b'D')) => Key::Steer(Direction::West),
+                    _ => continue,
+                },
+                b'p' => Key::Pause,
+                // Ctrl + C doesn't stop the program in raw mode, so it quits too
+                b'q' | 0x03 => Key::Quit,
+                _ => match Direction::from_wasd(&char::from(byte).to_string()) {
+                    Some(direction) => Key::Steer(direction),
+                    None => continue,
+                },
+            };
+
+            if sender.send(key).is_err() {
+                break;
+            }
+        }
+    });
+
+    receiver
+}
+
+/// The ANSI escape sequence that sets the text color.
+fn foreground(color: &Color) -> String {
+    format!(
+        "\x1b[38;2;{};{};{}m",
+        (color.r * 255.0) as u8,
+        (color.g * 255.0) as u8,
+        (color.b * 255.0) as u8
+    )
+}
+
+/// Draw one cell as two characters, which makes it about square in most terminals.
+fn draw_cell(cell: &Cell) -> String {
+    match cell {
+        Cell::Wall(_, color, glow, _) => {
+            // older segments fade into the dark, like in the browser
+            format!("{}██", foreground(&color.darken().mix(color, *glow)))
+        }
+        Cell::Player(_, color, _) => format!("{}▓▓", foreground(&color.lighten())),
+        Cell::Collision | Cell::Explosion(..) => format!("{}XX", foreground(&Color::yellow())),
+        Cell::Letter(letter, color) => format!("{}{} ", foreground(color), letter),
+        Cell::Pickup(_) => format!("{}()", foreground(&Color::white())),
+        Cell::Empty => "  ".to_string(),
+    }
+}
+
+fn draw(grid: &Grid, game_state: &GameState) -> String {
+    // in raw mode, a new line doesn't go back to the start of the line by itself
+    let mut frame = String::from("\x1b[H");
+
+    for y in 0..grid.height() {
+        for x in 0..grid.width() {
+            frame.push_str(&draw_cell(&grid.get(x, y)));
+        }
+        frame.push_str("\x1b[0m\r\n");
+    }
+
+    let scores: Vec<String> = game_state.players.iter().map(|p| p.score.to_string()).collect();
+    let status = match (game_state.phase, game_state.winner()) {
+        (Phase::Paused, _) => "Paused: P to resume".to_string(),
+        (Phase::GameOver, Some(winner)) => format!("Player {} wins: Q to quit", winner + 1),
+        (Phase::GameOver, None) => "Draw: Q to quit".to_string(),
+        _ => "Arrows or WASD to steer, P to pause, Q to quit".to_string(),
+    };
+    frame.push_str(&format!("{}   {}\x1b[K\r\n", scores.join(" - "), status));
+    frame
+}
+
+/// The time until the next tick, which depends on what the game is doing.
+fn tick_interval(game_state: &GameState) -> Duration {
+    // while paused, the keys are still read at the usual pace
+    let ms = game_state.tick_interval().unwrap_or(game_state.tick_interval_ms);
+    Duration::from_millis(u64::from(ms))
+}
+
+fn play(options: &Options, keys: &Receiver<Key>) -> io::Result<()> {
+    let (width, height) = ArenaSize::Medium.dimensions(ArenaShape::Classic);
+    let num_humans = if options.watch { 0 } else { 1 };
+    let colors = [Color::red(), Color::blue()];
+    let mut game_state =
+        GameState::new(num_humans, 2, options.bot_difficulty, 3, width, height, &colors);
+    if let Some(seed) = options.seed {
+        game_state.set_seed(seed);
+    }
+    let mut grid = Grid::new(width, height, &game_state);
+    let mut stdout = io::stdout();
+
+    loop {
+        for key in keys.try_iter() {
+            match key {
+                Key::Steer(direction) if num_humans > 0 => {
+                    game_state.players[0].set_direction(direction);
+                }
+                Key::Steer(_) => {}
+                Key::Pause => game_state.toggle_pause(),
+                Key::Quit => return Ok(()),
+            }
+        }
+
+        grid.reset(&game_state);
+        stdout.write_all(draw(&grid, &game_state).as_bytes())?;
+        stdout.flush()?;
+
+        thread::sleep(tick_interval(&game_state));
+        game_state.tick();
+    }
+}
+
+fn main() {
+    let options = match parse_options() {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    // a benchmark doesn't draw anything, so it doesn't need the terminal in raw mode
+    if let Some(games) = options.benchmark {
+        let bots = [options.bot_difficulty, options.against];
+        println!("{}", benchmark::self_play(bots, games, options.seed.unwrap_or(0)));
+        return;
+    }
+
+    // raw mode passes every key on right away, without echoing it
+    let saved = Command::new("stty")
+        .arg("-g")
+        .output()
+        .ok()
+        .filter(|output| output.status.success());
+    let _ = Command::new("stty").args(["raw", "-echo"]).status();
+    // switch to the alternate screen and hide the cursor
+    print!("\x1b[?1049h\x1b[?25l\x1b[2J");
+
+    let keys = read_keys();
+    let result = play(&options, &keys);
+
+    print!("\x1b[0m\x1b[?25h\x1b[?1049l");
+    let _ = io::stdout().flush();
+    match saved {
+        Some(saved) => {
+            let settings = String::from_utf8_lossy(&saved.stdout).trim().to_string();
+            let _ = Command::new("stty").arg(settings).status();
+        }
+        None => {
+            let _ = Command::new("stty").arg("sane").status();
+        }
+    }
+
+    if let Err(e) = result {
+        eprintln!("{}", e);
+    }
+}
@@ -0,0 +1,705 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::{
+    common::{Direction, Position},
+    game::{Controller, GameState},
+    rng::Rng,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::VecDeque,
+    fmt::{self, Display, Formatter},
+};
+
+/// How many moves the hard bot looks ahead, counting the moves of all players.
+const MINIMAX_DEPTH: usize = 4;
+
+/// How many cells ahead of its head the aggressor bot expects another player to go.
+const PREDICTED_PATH_LENGTH: usize = 6;
+
+/// The value of a position in which the round is decided. It is larger than any difference in room.
+const DECIDED: i64 = 1_000_000;
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum BotDifficulty {
+    Easy,
+    Medium,
+    Hard,
+    /// Not harder than hard, but a different style: this bot chases the other players.
+    Hunter,
+    /// Another style: this bot drives in front of a human player to cut off their space.
+    Aggressor,
+}
+
+impl BotDifficulty {
+    /// The next difficulty level, wrapping around from hardest to easiest.
+    pub fn next(&self) -> Self {
+        match self {
+            BotDifficulty::Easy => BotDifficulty::Medium,
+            BotDifficulty::Medium => BotDifficulty::Hard,
+            BotDifficulty::Hard => BotDifficulty::Hunter,
+            BotDifficulty::Hunter => BotDifficulty::Aggressor,
+            BotDifficulty::Aggressor => BotDifficulty::Easy,
+        }
+    }
+
+    /// The chance, from 0.0 to 1.0, that a bot reacts too late and keeps its current direction
+    /// instead of the direction of its choice. This makes the easier bots feel less perfect.
+    pub fn miss_chance(&self) -> f64 {
+        match self {
+            BotDifficulty::Easy => 0.1,
+            BotDifficulty::Medium => 0.03,
+            BotDifficulty::Hard | BotDifficulty::Hunter | BotDifficulty::Aggressor => 0.0,
+        }
+    }
+}
+
+impl Display for BotDifficulty {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            BotDifficulty::Easy => write!(f, "Easy"),
+            BotDifficulty::Medium => write!(f, "Medium"),
+            BotDifficulty::Hard => write!(f, "Hard"),
+            BotDifficulty::Hunter => write!(f, "Hunter"),
+            BotDifficulty::Aggressor => write!(f, "Aggressor"),
+        }
+    }
+}
+
+/// Choose the next direction of the active player, using the strategy that belongs to the
+/// specified difficulty. Now and then, depending on the difficulty, the bot misses its turn.
+pub fn next_direction(game_state: &GameState, difficulty: BotDifficulty, rng: &mut Rng) -> Direction {
+    let chance = difficulty.miss_chance();
+    if chance > 0.0 && rng.next_f64() < chance {
+        return game_state.players[game_state.active_player].segments.back().unwrap().1;
+    }
+
+    match difficulty {
+        BotDifficulty::Easy => drunk_lamppost_next(game_state, rng),
+        BotDifficulty::Medium => flood_fill_next(game_state),
+        BotDifficulty::Hard => minimax_next(game_state, MINIMAX_DEPTH),
+        BotDifficulty::Hunter => hunter_next(game_state),
+        BotDifficulty::Aggressor => aggressor_next(game_state),
+    }
+}
+
+/// What a bot thought of one of the directions that it could go next.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Consideration {
+    pub direction: Direction,
+    /// Whether the bot survives the next step in this direction.
+    pub safe: bool,
+    /// How the strategy of the bot ranks this direction, where higher is better. Directions that
+    /// the strategy doesn't rank have none, like unsafe directions, or the directions that the
+    /// easy bot picks from at random.
+    pub score: Option<i64>,
+}
+
+/// The direction that a bot chose, and what it thought of each direction that it could go.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Decision {
+    /// Where the head of the bot was when it decided.
+    pub position: Position,
+    pub direction: Direction,
+    pub considered: Vec<Consideration>,
+}
+
+/// Choose the next direction of the active player like `next_direction` does, and explain the
+/// choice. The explanation looks at every direction again, so this is meant for debugging.
+pub fn decide(game_state: &GameState, difficulty: BotDifficulty, rng: &mut Rng) -> Decision {
+    let direction = next_direction(game_state, difficulty, rng);
+    let me = game_state.active_player;
+    let (position, current_direction) = *game_state.players[me].segments.back().unwrap();
+
+    // the room after every safe step, like the strategies see it
+    let steps: Vec<_> = possible_directions(current_direction)
+        .into_iter()
+        .map(|direction| {
+            let mut cloned_state = game_state.clone();
+            cloned_state.simulate_step(direction);
+            let (head, _) = *cloned_state.players[me].segments.back().unwrap();
+            let room = (!cloned_state.has_collision()).then(|| reachable_cells(&cloned_state, head));
+            (direction, cloned_state, head, room)
+        })
+        .collect();
+
+    // the hunter and the aggressor don't rank the directions that leave too little room
+    let most_room = steps.iter().filter_map(|(_, _, _, room)| *room).max().unwrap_or(0);
+    let target = aggressor_target(game_state);
+
+    let considered = steps
+        .iter()
+        .map(|(direction, cloned_state, head, room)| {
+            let score = room.and_then(|room| match difficulty {
+                BotDifficulty::Easy => None,
+                BotDifficulty::Medium => Some(room as i64),
+                BotDifficulty::Hard => {
+                    Some(minimax_move(game_state, *direction, me, MINIMAX_DEPTH, i64::MIN, i64::MAX, 0))
+                }
+                _ if room * 2 < most_room => None,
+                BotDifficulty::Hunter => Some(-(distance_to_others(game_state, head) as i64)),
+                BotDifficulty::Aggressor => Some(match target {
+                    Some(target) => {
+                        let (target_head, _) = *cloned_state.players[target].segments.back().unwrap();
+                        -(reachable_cells(cloned_state, target_head) as i64)
+                    }
+                    None => room as i64,
+                }),
+            });
+
+            Consideration {
+                direction: *direction,
+                safe: room.is_some(),
+                score,
+            }
+        })
+        .collect();
+
+    Decision {
+        position,
+        direction,
+        considered,
+    }
+}
+
+/// The directions a player can go next: straight ahead, or a quarter turn to either side.
+fn possible_directions(current_direction: Direction) -> [Direction; 3] {
+    [
+        current_direction,
+        current_direction.turn_left(),
+        current_direction.turn_right(),
+    ]
+}
+
+/// Drunk lamppost bot. This bot will randomly choose a direction to go to, but
+/// will avoid collisions. It will also try to keep the current direction if
+/// possible. This is actually not really how a drunk would behave around a
+/// lamppost, but it's a little less crashy than a completely random bot.
+pub fn drunk_lamppost_next(game_state: &GameState, rng: &mut Rng) -> Direction {
+    let current_direction = game_state.players[game_state.active_player].segments.back().unwrap().1;
+    let mut acceptable_directions = Vec::new();
+
+    // find directions that don't result in a collision
+    for direction in possible_directions(current_direction) {
+        let mut cloned_state = game_state.clone();
+        cloned_state.simulate_step(direction);
+
+        if !cloned_state.has_collision() {
+            acceptable_directions.push(direction);
+        }
+    }
+
+    // if we're going to crash anyway, keep the current direction
+    if acceptable_directions.is_empty() {
+        return current_direction;
+    }
+
+    // if current direction is acceptable, keep it most of the time
+    if acceptable_directions.contains(&current_direction) && rng.next_f64() > 0.1 {
+        return current_direction;
+    }
+
+    // otherwise, pick a random direction from acceptable directions
+    acceptable_directions[rng.below(acceptable_directions.len())]
+}
+
+/// Flood fill bot. For each direction that doesn't result in a collision, this bot counts the
+/// cells it could still reach after moving in that direction, and picks the direction with the
+/// most room. This keeps it from driving into small pockets.
+pub fn flood_fill_next(game_state: &GameState) -> Direction {
+    let current_direction = game_state.players[game_state.active_player].segments.back().unwrap().1;
+    let mut best: Option<(Direction, usize)> = None;
+
+    for direction in possible_directions(current_direction) {
+        let mut cloned_state = game_state.clone();
+        cloned_state.simulate_step(direction);
+
+        if cloned_state.has_collision() {
+            continue;
+        }
+
+        let (head, _) = *cloned_state.players[cloned_state.active_player].segments.back().unwrap();
+        let room = reachable_cells(&cloned_state, head);
+
+        // prefer the current direction when there is a tie, to avoid needless wiggling
+        let is_better = match best {
+            None => true,
+            Some((_, best_room)) => {
+                room > best_room || (room == best_room && direction == current_direction)
+            }
+        };
+
+        if is_better {
+            best = Some((direction, room));
+        }
+    }
+
+    best.map_or(current_direction, |(direction, _)| direction)
+}
+
+/// Hunter bot. Of the directions that leave enough room, this bot picks the one that brings it
+/// closest to the cells around the head of another player, to cut them off. Enough room means at
+/// least half of the room of the roomiest direction, so it doesn't chase others into a dead end.
+pub fn hunter_next(game_state: &GameState) -> Direction {
+    let active_player = game_state.active_player;
+    let current_direction = game_state.players[active_player].segments.back().unwrap().1;
+    let mut candidates = Vec::new();
+
+    for direction in possible_directions(current_direction) {
+        let mut cloned_state = game_state.clone();
+        cloned_state.simulate_step(direction);
+
+        if cloned_state.has_collision() {
+            continue;
+        }
+
+        let (head, _) = *cloned_state.players[active_player].segments.back().unwrap();
+        let room = reachable_cells(&cloned_state, head);
+
+        let distance = distance_to_others(game_state, &head);
+
+        candidates.push((direction, room, distance));
+    }
+
+    let most_room = candidates.iter().map(|(_, room, _)| *room).max().unwrap_or(0);
+
+    candidates
+        .into_iter()
+        .filter(|(_, room, _)| *room * 2 >= most_room)
+        .min_by_key(|(direction, _, distance)| (*distance, *direction != current_direction))
+        .map_or(current_direction, |(direction, _, _)| direction)
+}
+
+/// The distance from the specified position to the closest cell around the head of another player
+/// than the active player. The hunter bot aims for these cells, where the others might go next.
+fn distance_to_others(game_state: &GameState, position: &Position) -> usize {
+    let (width, height) = (game_state.grid_width, game_state.grid_height);
+    game_state
+        .players
+        .iter()
+        .enumerate()
+        .filter(|(i, player)| *i != game_state.active_player && !player.eliminated)
+        .filter_map(|(_, player)| player.segments.back())
+        .flat_map(|(other_head, _)| other_head.neighbors(width, height))
+        .map(|target| game_state.distance(position, &target))
+        .min()
+        .unwrap_or(0)
+}
+
+/// Aggressor bot. This bot picks a target, predicts where the target is going and drives there,
+/// preferring the moves that leave the target the least room. Like the hunter bot, it only
+/// considers moves that leave itself at least half of the room of the roomiest move, so it never
+/// drives into a crash or a dead end when there is a way out.
+pub fn aggressor_next(game_state: &GameState) -> Direction {
+    let active_player = game_state.active_player;
+    let current_direction = game_state.players[active_player].segments.back().unwrap().1;
+
+    let Some(target) = aggressor_target(game_state) else {
+        return flood_fill_next(game_state);
+    };
+    let path = predicted_path(game_state, target);
+    let mut candidates = Vec::new();
+
+    for direction in possible_directions(current_direction) {
+        let mut cloned_state = game_state.clone();
+        cloned_state.simulate_step(direction);
+
+        if cloned_state.has_collision() {
+            continue;
+        }
+
+        let (head, _) = *cloned_state.players[active_player].segments.back().unwrap();
+        let (target_head, _) = *cloned_state.players[target].segments.back().unwrap();
+        let room = reachable_cells(&cloned_state, head);
+        let target_room = reachable_cells(&cloned_state, target_head);
+        let distance = path
+            .iter()
+            .map(|cell| game_state.distance(&head, cell))
+            .min()
+            .unwrap_or(0);
+
+        candidates.push((direction, room, target_room, distance));
+    }
+
+    let most_room = candidates.iter().map(|(_, room, _, _)| *room).max().unwrap_or(0);
+
+    candidates
+        .into_iter()
+        .filter(|(_, room, _, _)| *room * 2 >= most_room)
+        .min_by_key(|(direction, _, target_room, distance)| {
+            (*target_room, *distance, *direction != current_direction)
+        })
+        .map_or(current_direction, |(direction, _, _, _)| direction)
+}
+
+/// The player that the aggressor bot goes after: the first human player, or if everyone is a bot,
+/// the next player in turn. Players that are out of the round are left alone. Returns `None` if
+/// there is nobody else.
+fn aggressor_target(game_state: &GameState) -> Option<usize> {
+    let active_player = game_state.active_player;
+    let mut others = (1..game_state.players.len())
+        .map(|offset| (active_player + offset) % game_state.players.len())
+        .filter(|i| !game_state.players[*i].eliminated);
+
+    others
+        .clone()
+        .find(|i| !matches!(game_state.players[*i].controller, Controller::Bot(_)))
+        .or_else(|| others.next())
+}
+
+/// The empty cells that the specified player drives through when it goes straight ahead.
+fn predicted_path(game_state: &GameState, player: usize) -> Vec<Position> {
+    let (mut position, direction) = *game_state.players[player].segments.back().unwrap();
+    let mut path = Vec::with_capacity(PREDICTED_PATH_LENGTH);
+
+    while path.len() < PREDICTED_PATH_LENGTH {
+        match game_state.next_position(&position, &direction) {
+            Some(next) if !game_state.is_occupied(&next) => {
+                path.push(next);
+                position = next;
+            }
+            _ => break,
+        }
+    }
+
+    path
+}
+
+/// Count the empty cells that can be reached from the specified position, not counting the
+/// position itself. Obstacles and the segments of all players are blocked, and movement follows
+/// the edges of the arena the same way it does for players.
+pub fn reachable_cells(game_state: &GameState, from: Position) -> usize {
+    let mut visited = blocked_cells(game_state);
+    visited[from.y * game_state.grid_width + from.x] = true;
+    fill(game_state, &mut visited, from)
+}
+
+/// Find the separate regions of empty cells around the specified position, and count the cells of
+/// each. A move that leaves more than one region splits the free space, and a player can only
+/// drive into one of them: the other regions are lost, however large they are. This is what
+/// `reachable_cells` doesn't see, since it counts the cells of all regions together.
+pub fn region_sizes(game_state: &GameState, from: Position) -> Vec<usize> {
+    let width = game_state.grid_width;
+    let mut visited = blocked_cells(game_state);
+    visited[from.y * width + from.x] = true;
+
+    Direction::ALL
+        .into_iter()
+        .filter_map(|direction| game_state.next_position(&from, &direction))
+        .filter_map(|next| {
+            let index = next.y * width + next.x;
+            if visited[index] {
+                return None;
+            }
+            visited[index] = true;
+            Some(1 + fill(game_state, &mut visited, next))
+        })
+        .collect()
+}
+
+/// Count the empty cells that each player can reach before any other player, which is the room
+/// that the player has to itself. The free space is filled from the heads of all players at once,
+/// and a cell that several players reach at the same time, or that can only be reached through
+/// such a cell, belongs to nobody.
+pub fn territories(game_state: &GameState) -> Vec<usize> {
+    const NOBODY: usize = usize::MAX;
+
+    let width = game_state.grid_width;
+    let blocked = blocked_cells(game_state);
+    let mut distance = vec![usize::MAX; blocked.len()];
+    let mut owner = vec![NOBODY; blocked.len()];
+    let mut queue = VecDeque::new();
+
+    // the players that are out don't claim anything
+    for (i, player) in game_state.players.iter().enumerate().filter(|(_, p)| !p.eliminated) {
+        if let Some((head, _)) = player.segments.back() {
+            distance[head.y * width + head.x] = 0;
+            owner[head.y * width + head.x] = i;
+            queue.push_back(*head);
+        }
+    }
+
+    let mut territories = vec![0; game_state.players.len()];
+    while let Some(position) = queue.pop_front() {
+        let index = position.y * width + position.x;
+        for direction in Direction::ALL {
+            let Some(next) = game_state.next_position(&position, &direction) else {
+                continue;
+            };
+            let next_index = next.y * width + next.x;
+
+            if blocked[next_index] {
+                continue;
+            }
+
+            if distance[next_index] == usize::MAX {
+                distance[next_index] = distance[index] + 1;
+                owner[next_index] = owner[index];
+                queue.push_back(next);
+            } else if distance[next_index] == distance[index] + 1
+                && owner[next_index] != owner[index]
+            {
+                owner[next_index] = NOBODY;
+            }
+        }
+
+        // the owner of a cell is final by the time it is visited, because all cells that are
+        // closer to the heads have been visited before it
+        if owner[index] != NOBODY && distance[index] > 0 {
+            territories[owner[index]] += 1;
+        }
+    }
+
+    territories
+}
+
+/// For every cell, whether it holds an obstacle or a segment of a player.
+fn blocked_cells(game_state: &GameState) -> Vec<bool> {
+    let width = game_state.grid_width;
+    let mut blocked = vec![false; width * game_state.grid_height];
+
+    for obstacle in &game_state.obstacles {
+        blocked[obstacle.y * width + obstacle.x] = true;
+    }
+
+    for player in &game_state.players {
+        for (position, _) in &player.segments {
+            blocked[position.y * width + position.x] = true;
+        }
+    }
+
+    blocked
+}
+
+/// Visit all cells that can be reached from the specified position without passing through visited
+/// cells, and count them.
+fn fill(game_state: &GameState, visited: &mut [bool], from: Position) -> usize {
+    let width = game_state.grid_width;
+    let mut count = 0;
+    let mut queue = VecDeque::from(vec![from]);
+
+    while let Some(position) = queue.pop_front() {
+        for direction in Direction::ALL {
+            let Some(next) = game_state.next_position(&position, &direction) else {
+                continue;
+            };
+            let index = next.y * width + next.x;
+
+            if !visited[index] {
+                visited[index] = true;
+                count += 1;
+                queue.push_back(next);
+            }
+        }
+    }
+
+    count
+}
+
+/// Minimax bot. This bot looks `depth` moves ahead, taking turns with the other players, and
+/// assumes that every other player makes the move that is worst for this bot. Positions are valued
+/// by how much more room this bot has than the roomiest of the others, where the room of a player
+/// that has split the free space is only its largest region. Moves that can't change the outcome
+/// are pruned, to stay well within the time of one tick. Ties are broken by the flood fill strategy.
+pub fn minimax_next(game_state: &GameState, depth: usize) -> Direction {
+    let me = game_state.active_player;
+    let preferred_direction = flood_fill_next(game_state);
+    let current_direction = game_state.players[me].segments.back().unwrap().1;
+    let mut best: Option<(Direction, i64)> = None;
+
+    for direction in possible_directions(current_direction) {
+        // a move that is worse than the best so far can be cut short, but a move that is as good
+        // must get its exact value, so the tie is only broken between moves that really are equal
+        let alpha = best.map_or(i64::MIN, |(_, value)| value.saturating_sub(1));
+        let value = minimax_move(game_state, direction, me, depth, alpha, i64::MAX, 0);
+
+        let is_better = match best {
+            None => true,
+            Some((_, best_value)) => {
+                value > best_value || (value == best_value && direction == preferred_direction)
+            }
+        };
+
+        if is_better {
+            best = Some((direction, value));
+        }
+    }
+
+    best.map_or(preferred_direction, |(direction, _)| direction)
+}
+
+/// The value for player `me` of letting the active player move in the specified direction, and
+/// then playing on for `depth - 1` more moves. `ply` counts the moves made so far.
+fn minimax_move(
+    game_state: &GameState,
+    direction: Direction,
+    me: usize,
+    depth: usize,
+    alpha: i64,
+    beta: i64,
+    ply: i64,
+) -> i64 {
+    let mut cloned_state = game_state.clone();
+    cloned_state.simulate_step(direction);
+
+    let crashed = cloned_state.crashed_players();
+    if !crashed.is_empty() {
+        // losing later is better than losing now, and winning now is better than winning later
+        if crashed.contains(&me) {
+            return -DECIDED + ply;
+        }
+
+        // the others play on without the players that crashed, so this bot has only won when it
+        // is the last one left
+        for i in crashed {
+            cloned_state.players[i].eliminated = true;
+        }
+        if cloned_state.remaining_players().len() <= 1 {
+            return DECIDED - ply;
+        }
+    }
+
+    if depth <= 1 {
+        return room_difference(&cloned_state, me);
+    }
+
+    cloned_state.set_next_player();
+    let active_player = cloned_state.active_player;
+    let current_direction = cloned_state.players[active_player].segments.back().unwrap().1;
+    let maximizing = active_player == me;
+    let (mut alpha, mut beta) = (alpha, beta);
+    let mut best = if maximizing { i64::MIN } else { i64::MAX };
+
+    for next_direction in possible_directions(current_direction) {
+        let value =
+            minimax_move(&cloned_state, next_direction, me, depth - 1, alpha, beta, ply + 1);
+
+        if maximizing {
+            best = best.max(value);
+            alpha = alpha.max(best);
+        } else {
+            best = best.min(value);
+            beta = beta.min(best);
+        }
+
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    best
+}
+
+/// How many more cells player `me` can reach than the other player that can reach the most. Only
+/// the largest region around each head counts, so that moves which cut a player off from an escape
+/// route are valued lower.
+fn room_difference(game_state: &GameState, me: usize) -> i64 {
+    let room = |i: usize| {
+        let (head, _) = *game_state.players[i].segments.back().unwrap();
+        region_sizes(game_state, head).into_iter().max().unwrap_or(0) as i64
+    };
+
+    let most_room_of_others = (0..game_state.players.len())
+        .filter(|i| *i != me && !game_state.players[*i].eliminated)
+        .map(room)
+        .max()
+        .unwrap_or(0);
+
+    room(me) - most_room_of_others
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::benchmark;
+    use crate::common::Color;
+    use crate::game::{ArenaLayout, ArenaMode, Phase};
+
+    /// Bots of the specified difficulty on an empty arena of 20 by 16, one for every head,
+    /// heading north from there, with the first bot to move.
+    fn bots(difficulty: BotDifficulty, arena_mode: ArenaMode, heads: &[Position]) -> GameState {
+        let colors = [Color::red(), Color::blue(), Color::green()];
+        let mut game_state = GameState::new(0, heads.len(), difficulty, 3, 20, 16, &colors);
+        game_state.set_arena(arena_mode, ArenaLayout::Border);
+        for (player, head) in game_state.players.iter_mut().zip(heads) {
+            player.segments = VecDeque::from([(*head, Direction::North)]);
+        }
+        game_state.phase = Phase::Step;
+        game_state.active_player = 0;
+        game_state.count_occupied();
+        game_state
+    }
+
+    /// Three bots on the walled arena of 20 by 16, where the second one is boxed into the top left
+    /// corner by its own trail, so that every move that it can make is a crash.
+    fn boxed_in_second_bot(num_players: usize) -> GameState {
+        let heads = [Position { x: 10, y: 8 }, Position { x: 1, y: 1 }, Position { x: 15, y: 8 }];
+        let mut game_state =
+            bots(BotDifficulty::Hard, ArenaMode::Walled, &heads[..num_players]);
+        game_state.players[1].segments.push_front((Position { x: 2, y: 1 }, Direction::West));
+        game_state.count_occupied();
+        game_state
+    }
+
+    #[test]
+    fn minimax_wins_when_the_only_other_player_crashes() {
+        let game_state = boxed_in_second_bot(2);
+        let value = minimax_move(&game_state, Direction::North, 0, 2, i64::MIN, i64::MAX, 0);
+        assert_eq!(value, DECIDED - 1);
+    }
+
+    #[test]
+    fn minimax_plays_on_when_one_of_the_other_players_crashes() {
+        let game_state = boxed_in_second_bot(3);
+        let value = minimax_move(&game_state, Direction::North, 0, 2, i64::MIN, i64::MAX, 0);
+        assert!(value.abs() < DECIDED / 2, "{} is decided", value);
+    }
+
+    #[test]
+    fn minimax_beats_the_drunk_bot_in_most_games() {
+        let games = 50;
+        let bots = [BotDifficulty::Hard, BotDifficulty::Easy];
+        let benchmark = benchmark::self_play(bots, games, 300);
+        assert!(benchmark.win_rate(0) >= 0.8, "{}", benchmark);
+    }
+
+    #[test]
+    fn hunter_goes_across_the_edge_in_a_wrapping_arena() {
+        let heads = [Position { x: 2, y: 8 }, Position { x: 17, y: 8 }];
+
+        let walled = bots(BotDifficulty::Hunter, ArenaMode::Walled, &heads);
+        assert_eq!(hunter_next(&walled), Direction::East);
+
+        let wrapping = bots(BotDifficulty::Hunter, ArenaMode::Wrap, &heads);
+        assert_eq!(hunter_next(&wrapping), Direction::West);
+    }
+
+    #[test]
+    fn distance_to_others_goes_across_the_edge_in_a_wrapping_arena() {
+        let heads = [Position { x: 2, y: 8 }, Position { x: 17, y: 8 }];
+        let from = Position { x: 0, y: 8 };
+
+        let walled = bots(BotDifficulty::Hunter, ArenaMode::Walled, &heads);
+        assert_eq!(distance_to_others(&walled, &from), 16);
+
+        let wrapping = bots(BotDifficulty::Hunter, ArenaMode::Wrap, &heads);
+        assert_eq!(distance_to_others(&wrapping, &from), 2);
+    }
+}
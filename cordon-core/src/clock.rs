@@ -0,0 +1,144 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::game::GameState;
+
+/// The most ticks that can be due at once. A game that fell far behind, like an online game that
+/// waited a long time for the other player, catches up by one extra tick instead of racing
+/// through every tick that it missed.
+const MAX_DUE_TICKS: u32 = 2;
+
+/// Keeps time for a game that is driven by a timer with a fixed rate, whatever the game is doing.
+/// Every phase of the game has its own time between ticks, and the clock tells when the next tick
+/// is due. Timing this way doesn't need a new timer for every phase, and it follows the time that
+/// the clock was told about, not the time on the wall.
+#[derive(Clone, Debug, Default)]
+pub struct Clock {
+    /// The time since the last tick in milliseconds.
+    elapsed_ms: u32,
+    /// The time between two ticks in the phase that the game was in when the clock last ran.
+    interval_ms: Option<u32>,
+}
+
+impl Clock {
+    /// Let `ms` milliseconds pass, and return whether the next tick of the game is due. While the
+    /// game is paused or over, ticks don't change anything, so the clock stands still.
+    pub fn run(&mut self, ms: u32, game_state: &GameState) -> bool {
+        self.interval_ms = game_state.tick_interval();
+
+        match self.interval_ms {
+            Some(interval_ms) => {
+                let max_elapsed_ms = interval_ms.saturating_mul(MAX_DUE_TICKS);
+                self.elapsed_ms = self.elapsed_ms.saturating_add(ms).min(max_elapsed_ms);
+                self.elapsed_ms >= interval_ms
+            }
+            None => {
+                self.elapsed_ms = 0;
+                false
+            }
+        }
+    }
+
+    /// Start waiting for the next tick, after the game made the tick that was due. A tick that
+    /// came late, like in an online game that waited for the other player, leaves less time
+    /// until the next one, so the game catches up, with `MAX_DUE_TICKS` due at once at the most.
+    pub fn ticked(&mut self) {
+        self.elapsed_ms = self.elapsed_ms.saturating_sub(self.interval_ms.unwrap_or(0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::BotDifficulty;
+    use crate::common::Color;
+    use crate::game::Phase;
+
+    fn game_in(phase: Phase) -> GameState {
+        let colors = [Color::red(), Color::blue()];
+        let mut game_state = GameState::new(0, 2, BotDifficulty::Easy, 3, 16, 12, &colors);
+        game_state.set_phase(phase);
+        game_state
+    }
+
+    #[test]
+    fn tick_is_due_after_the_interval_of_the_phase() {
+        for phase in [Phase::Step, Phase::Countdown, Phase::Score] {
+            let game_state = game_in(phase);
+            let interval_ms = game_state.tick_interval().unwrap();
+            let mut clock = Clock::default();
+
+            assert!(!clock.run(interval_ms - 1, &game_state), "{:?}", phase);
+            assert!(clock.run(1, &game_state), "{:?}", phase);
+
+            // after the tick, the clock waits for the whole interval again
+            clock.ticked();
+            assert!(!clock.run(interval_ms - 1, &game_state), "{:?}", phase);
+            assert!(clock.run(1, &game_state), "{:?}", phase);
+        }
+    }
+
+    #[test]
+    fn clock_stands_still_and_starts_over_while_the_game_is_paused_or_over() {
+        let moving = game_in(Phase::Step);
+        let interval_ms = moving.tick_interval().unwrap();
+
+        for phase in [Phase::Paused, Phase::GameOver] {
+            let mut clock = Clock::default();
+            assert!(!clock.run(interval_ms - 1, &moving));
+
+            let standing_still = game_in(phase);
+            assert!(!clock.run(interval_ms * 10, &standing_still), "{:?}", phase);
+
+            // the time before the pause doesn't count anymore afterwards
+            assert!(!clock.run(interval_ms - 1, &moving), "{:?}", phase);
+            assert!(clock.run(1, &moving), "{:?}", phase);
+        }
+    }
+
+    #[test]
+    fn ticked_keeps_the_time_that_was_left_over() {
+        let game_state = game_in(Phase::Step);
+        let interval_ms = game_state.tick_interval().unwrap();
+        let mut clock = Clock::default();
+
+        assert!(clock.run(interval_ms + 30, &game_state));
+        clock.ticked();
+
+        // the late tick leaves 30 milliseconds less until the next one
+        assert!(!clock.run(interval_ms - 31, &game_state));
+        assert!(clock.run(1, &game_state));
+    }
+
+    #[test]
+    fn clock_catches_up_by_one_extra_tick_at_the_most() {
+        let game_state = game_in(Phase::Step);
+        let interval_ms = game_state.tick_interval().unwrap();
+        let mut clock = Clock::default();
+
+        // the game waited for ten ticks, but only makes one extra tick to catch up
+        assert!(clock.run(interval_ms * 10, &game_state));
+        clock.ticked();
+        assert!(clock.run(0, &game_state));
+        clock.ticked();
+        assert!(!clock.run(0, &game_state));
+        assert!(!clock.run(interval_ms - 1, &game_state));
+        assert!(clock.run(1, &game_state));
+    }
+}
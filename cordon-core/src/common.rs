@@ -0,0 +1,701 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Debug, Display, Formatter};
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Direction {
+    North,
+    South,
+    West,
+    East,
+}
+
+/// The keys that steer in each direction, with the names that browsers give them.
+pub type KeyMap = [(Direction, &'static str); 4];
+
+/// The W, A, S and D keys, on the left of the keyboard.
+pub const WASD_KEYS: KeyMap = [
+    (Direction::North, "w"),
+    (Direction::West, "a"),
+    (Direction::South, "s"),
+    (Direction::East, "d"),
+];
+
+/// The arrow keys.
+pub const ARROW_KEYS: KeyMap = [
+    (Direction::North, "ArrowUp"),
+    (Direction::West, "ArrowLeft"),
+    (Direction::South, "ArrowDown"),
+    (Direction::East, "ArrowRight"),
+];
+
+/// The I, J, K and L keys, on the right of the keyboard.
+pub const IJKL_KEYS: KeyMap = [
+    (Direction::North, "i"),
+    (Direction::West, "j"),
+    (Direction::South, "k"),
+    (Direction::East, "l"),
+];
+
+/// The T, F, G and H keys, in the middle of the keyboard.
+pub const TFGH_KEYS: KeyMap = [
+    (Direction::North, "t"),
+    (Direction::West, "f"),
+    (Direction::South, "g"),
+    (Direction::East, "h"),
+];
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ];
+
+    /// The direction that the specified key steers in with the specified keys, or `None` if it
+    /// isn't one of them. Letters match in either case.
+    pub fn from_key(keys: &KeyMap, key: &str) -> Option<Direction> {
+        keys.iter()
+            .find(|(_, k)| k.eq_ignore_ascii_case(key))
+            .map(|(direction, _)| *direction)
+    }
+
+    /// The direction that the specified key steers in with the W, A, S and D keys.
+    pub fn from_wasd(key: &str) -> Option<Direction> {
+        Direction::from_key(&WASD_KEYS, key)
+    }
+
+    /// The direction that the specified key steers in with the arrow keys.
+    pub fn from_arrows(key: &str) -> Option<Direction> {
+        Direction::from_key(&ARROW_KEYS, key)
+    }
+
+    /// The direction that points the other way.
+    pub fn opposite(&self) -> Direction {
+        match self {
+            Direction::North => Direction::South,
+            Direction::South => Direction::North,
+            Direction::West => Direction::East,
+            Direction::East => Direction::West,
+        }
+    }
+
+    /// The direction after making a quarter turn to the left.
+    pub fn turn_left(&self) -> Direction {
+        match self {
+            Direction::North => Direction::West,
+            Direction::West => Direction::South,
+            Direction::South => Direction::East,
+            Direction::East => Direction::North,
+        }
+    }
+
+    /// The direction after making a quarter turn to the right.
+    pub fn turn_right(&self) -> Direction {
+        match self {
+            Direction::North => Direction::East,
+            Direction::East => Direction::South,
+            Direction::South => Direction::West,
+            Direction::West => Direction::North,
+        }
+    }
+}
+
+/// How the line through a trail is drawn, so that players can be told apart without their colors.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum TrailPattern {
+    #[default]
+    Solid,
+    Dashed,
+    Dotted,
+}
+
+impl TrailPattern {
+    /// The next trail pattern, wrapping around from the last to the first.
+    pub fn next(&self) -> Self {
+        match self {
+            TrailPattern::Solid => TrailPattern::Dashed,
+            TrailPattern::Dashed => TrailPattern::Dotted,
+            TrailPattern::Dotted => TrailPattern::Solid,
+        }
+    }
+}
+
+impl Display for TrailPattern {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            TrailPattern::Solid => write!(f, "Solid"),
+            TrailPattern::Dashed => write!(f, "Dashed"),
+            TrailPattern::Dotted => write!(f, "Dotted"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Color {
+    pub r: f64,
+    pub g: f64,
+    pub b: f64,
+    /// Opacity, from 0.0 (transparent) to 1.0 (opaque).
+    #[serde(default = "full_opacity")]
+    pub a: f64,
+}
+
+fn full_opacity() -> f64 {
+    1.0
+}
+
+impl Color {
+    // Colors from the palette by Okabe and Ito, which stay distinguishable with the common kinds
+    // of color blindness.
+    pub const ORANGE: Color = Color::from_rgb8(230, 159, 0);
+    pub const SKY_BLUE: Color = Color::from_rgb8(86, 180, 233);
+    pub const BLUISH_GREEN: Color = Color::from_rgb8(0, 158, 115);
+    pub const SAFE_YELLOW: Color = Color::from_rgb8(240, 228, 66);
+    pub const SAFE_BLUE: Color = Color::from_rgb8(0, 114, 178);
+    pub const VERMILLION: Color = Color::from_rgb8(213, 94, 0);
+    pub const REDDISH_PURPLE: Color = Color::from_rgb8(204, 121, 167);
+
+    /// Create an opaque color from red, green and blue values between 0 and 255.
+    pub const fn from_rgb8(r: u8, g: u8, b: u8) -> Self {
+        Self {
+            r: r as f64 / 255.0,
+            g: g as f64 / 255.0,
+            b: b as f64 / 255.0,
+            a: 1.0,
+        }
+    }
+
+    pub fn black() -> Self {
+        Self {
+            r: 0.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        }
+    }
+
+    pub fn white() -> Self {
+        Self {
+            r: 1.0,
+            g: 1.0,
+            b: 1.0,
+            a: 1.0,
+        }
+    }
+
+    pub fn red() -> Self {
+        Self {
+            r: 1.0,
+            g: 0.0,
+            b: 0.0,
+            a: 1.0,
+        }
+    }
+
+    pub fn green() -> Self {
+        Self {
+            r: 0.0,
+            g: 1.0,
+            b: 0.0,
+            a: 1.0,
+        }
+    }
+
+    pub fn blue() -> Self {
+        Self {
+            r: 0.0,
+            g: 0.0,
+            b: 1.0,
+            a: 1.0,
+        }
+    }
+
+    pub fn yellow() -> Self {
+        Self {
+            r: 1.0,
+            g: 1.0,
+            b: 0.0,
+            a: 1.0,
+        }
+    }
+
+    pub fn darken(&self) -> Self {
+        Self {
+            r: self.r * 0.5,
+            g: self.g * 0.5,
+            b: self.b * 0.5,
+            a: self.a,
+        }
+    }
+
+    /// Create a color from hue (in degrees), saturation and lightness (both between 0.0 and 1.0).
+    pub fn from_hsl(h: f64, s: f64, l: f64) -> Self {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let s = s.clamp(0.0, 1.0);
+        let l = l.clamp(0.0, 1.0);
+
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = l - chroma / 2.0;
+
+        Self {
+            r: r + m,
+            g: g + m,
+            b: b + m,
+            a: 1.0,
+        }
+    }
+
+    /// Generate `n` distinct colors, with hues spread evenly around the color wheel, starting
+    /// at red.
+    pub fn palette(n: usize) -> Vec<Color> {
+        (0..n)
+            .map(|i| Color::from_hsl(i as f64 * 360.0 / n as f64, 1.0, 0.5))
+            .collect()
+    }
+
+    /// The colors that players can choose from.
+    pub fn swatches() -> [Color; 6] {
+        [
+            Color::red(),
+            Color::blue(),
+            Color::green(),
+            Color::white(),
+            Color::yellow(),
+            Color::red().mix(&Color::blue(), 0.5),
+        ]
+    }
+
+    /// Blend this color with another color. A `t` of 0.0 gives this color, and 1.0 gives the
+    /// other color.
+    pub fn mix(&self, other: &Color, t: f64) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        Self {
+            r: self.r + (other.r - self.r) * t,
+            g: self.g + (other.g - self.g) * t,
+            b: self.b + (other.b - self.b) * t,
+            a: self.a + (other.a - self.a) * t,
+        }
+    }
+
+    pub fn lighten(&self) -> Self {
+        Self {
+            r: f64::min(self.r * 1.5, 1.0),
+            g: f64::min(self.g * 1.5, 1.0),
+            b: f64::min(self.b * 1.5, 1.0),
+            a: self.a,
+        }
+    }
+
+    /// The same color with the specified opacity.
+    pub fn with_alpha(&self, a: f64) -> Self {
+        Self {
+            a: a.clamp(0.0, 1.0),
+            ..*self
+        }
+    }
+}
+
+impl Display for Color {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if self.a < 1.0 {
+            write!(
+                f,
+                "rgba({}, {}, {}, {})",
+                self.r * 255.0,
+                self.g * 255.0,
+                self.b * 255.0,
+                self.a
+            )
+        } else {
+            write!(
+                f,
+                "rgb({}, {}, {})",
+                self.r * 255.0,
+                self.g * 255.0,
+                self.b * 255.0
+            )
+        }
+    }
+}
+
+impl Default for Color {
+    fn default() -> Self {
+        Color {
+            r: 0.0,
+            g: 0.5,
+            b: 0.0,
+            a: 1.0,
+        }
+    }
+}
+
+/// The set of colors that players, obstacles and crashes are drawn in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum Palette {
+    #[default]
+    Standard,
+    /// Colors that can be told apart with color blindness, with more contrast between them.
+    ColorBlind,
+}
+
+impl Palette {
+    /// The other palette.
+    pub fn next(&self) -> Self {
+        match self {
+            Palette::Standard => Palette::ColorBlind,
+            Palette::ColorBlind => Palette::Standard,
+        }
+    }
+
+    /// The colors that players can choose from, in the order that they are handed out.
+    pub fn swatches(&self) -> Vec<Color> {
+        match self {
+            Palette::Standard => Color::swatches().to_vec(),
+            // blue and yellow are left out, because the obstacles and crashes use them
+            Palette::ColorBlind => vec![
+                Color::ORANGE,
+                Color::SKY_BLUE,
+                Color::BLUISH_GREEN,
+                Color::REDDISH_PURPLE,
+                Color::VERMILLION,
+                Color::white(),
+            ],
+        }
+    }
+
+    /// The color of the obstacles in the arena.
+    pub fn obstacle(&self) -> Color {
+        match self {
+            Palette::Standard => Color::default(),
+            Palette::ColorBlind => Color::SAFE_BLUE,
+        }
+    }
+
+    /// The color that highlights where a player crashed.
+    pub fn collision(&self) -> Color {
+        match self {
+            Palette::Standard => Color::yellow(),
+            Palette::ColorBlind => Color::SAFE_YELLOW,
+        }
+    }
+}
+
+impl Display for Palette {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Palette::Standard => write!(f, "Standard"),
+            Palette::ColorBlind => write!(f, "Color-Blind Safe"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Position {
+    pub x: usize,
+    pub y: usize,
+}
+
+impl Position {
+    /// Determine the next position based on the current position and direction. Wraps around when
+    /// the position is outside the grid.
+    pub fn next(&self, direction: &Direction, width: usize, height: usize) -> Self {
+        match direction {
+            Direction::North => Position {
+                x: self.x,
+                y: if self.y == 0 { height - 1 } else { self.y - 1 },
+            },
+            Direction::South => Position {
+                x: self.x,
+                y: if self.y == height - 1 { 0 } else { self.y + 1 },
+            },
+            Direction::West => Position {
+                x: if self.x == 0 { width - 1 } else { self.x - 1 },
+                y: self.y,
+            },
+            Direction::East => Position {
+                x: if self.x == width - 1 { 0 } else { self.x + 1 },
+                y: self.y,
+            },
+        }
+    }
+
+    /// Determine the next position based on the current position and direction, without wrapping
+    /// around. Returns `None` when the next position would be outside the grid.
+    pub fn next_within(&self, direction: &Direction, width: usize, height: usize) -> Option<Self> {
+        let (x, y) = match direction {
+            Direction::North => (Some(self.x), self.y.checked_sub(1)),
+            Direction::South => (Some(self.x), Some(self.y + 1).filter(|y| *y < height)),
+            Direction::West => (self.x.checked_sub(1), Some(self.y)),
+            Direction::East => (Some(self.x + 1).filter(|x| *x < width), Some(self.y)),
+        };
+
+        Some(Position { x: x?, y: y? })
+    }
+
+    /// The number of steps between two positions, moving only horizontally and vertically.
+    pub fn manhattan_distance(&self, other: &Position) -> usize {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// The number of steps between two positions, moving only horizontally and vertically, when
+    /// moving across the edges of the grid is allowed.
+    pub fn wrapping_distance(&self, other: &Position, width: usize, height: usize) -> usize {
+        let dx = self.x.abs_diff(other.x);
+        let dy = self.y.abs_diff(other.y);
+        dx.min(width - dx) + dy.min(height - dy)
+    }
+
+    /// The four positions next to this one, in the order of [`Direction::ALL`]. Wraps around when
+    /// a neighbor is outside the grid.
+    pub fn neighbors(&self, width: usize, height: usize) -> [Position; 4] {
+        Direction::ALL.map(|direction| self.next(&direction, width, height))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opposite_reverses_every_heading() {
+        assert_eq!(Direction::North.opposite(), Direction::South);
+        assert_eq!(Direction::South.opposite(), Direction::North);
+        assert_eq!(Direction::West.opposite(), Direction::East);
+        assert_eq!(Direction::East.opposite(), Direction::West);
+
+        for direction in Direction::ALL {
+            assert_eq!(direction.opposite().opposite(), direction);
+        }
+    }
+
+    #[test]
+    fn turns_go_around_every_heading() {
+        assert_eq!(Direction::North.turn_left(), Direction::West);
+        assert_eq!(Direction::West.turn_left(), Direction::South);
+        assert_eq!(Direction::South.turn_left(), Direction::East);
+        assert_eq!(Direction::East.turn_left(), Direction::North);
+
+        assert_eq!(Direction::North.turn_right(), Direction::East);
+        assert_eq!(Direction::East.turn_right(), Direction::South);
+        assert_eq!(Direction::South.turn_right(), Direction::West);
+        assert_eq!(Direction::West.turn_right(), Direction::North);
+
+        for direction in Direction::ALL {
+            assert_eq!(direction.turn_left().turn_right(), direction);
+            assert_eq!(direction.turn_left().turn_left(), direction.opposite());
+            assert_eq!(direction.turn_right().turn_right(), direction.opposite());
+        }
+    }
+
+    fn is_in_range(color: &Color) -> bool {
+        [color.r, color.g, color.b, color.a].iter().all(|c| (0.0..=1.0).contains(c))
+    }
+
+    /// Whether two colors differ noticeably in at least one channel.
+    fn is_distinct(a: &Color, b: &Color) -> bool {
+        [a.r - b.r, a.g - b.g, a.b - b.b].iter().any(|d| d.abs() > 0.1)
+    }
+
+    #[test]
+    fn palette_colors_are_distinct_and_in_range() {
+        for n in 1..=12 {
+            let palette = Color::palette(n);
+            assert_eq!(palette.len(), n);
+
+            for (i, color) in palette.iter().enumerate() {
+                assert!(is_in_range(color), "color {} of {} is {:?}", i, n, color);
+                for other in &palette[i + 1..] {
+                    assert!(is_distinct(color, other), "{:?} and {:?} of {}", color, other, n);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn swatches_are_distinct_and_in_range() {
+        let swatches = Color::swatches();
+
+        for (i, color) in swatches.iter().enumerate() {
+            assert!(is_in_range(color), "swatch {} is {:?}", i, color);
+            for other in &swatches[i + 1..] {
+                assert!(is_distinct(color, other), "{:?} and {:?}", color, other);
+            }
+        }
+    }
+
+    #[test]
+    fn from_hsl_gives_the_primary_colors() {
+        assert_eq!(Color::from_hsl(0.0, 1.0, 0.5), Color::red());
+        assert_eq!(Color::from_hsl(120.0, 1.0, 0.5), Color::green());
+        assert_eq!(Color::from_hsl(240.0, 1.0, 0.5), Color::blue());
+        assert_eq!(Color::from_hsl(360.0, 1.0, 0.5), Color::red());
+        assert_eq!(Color::from_hsl(0.0, 0.0, 1.0), Color::white());
+    }
+
+    /// The channels of a color that is written like `rgb(255, 0, 127.5)` or
+    /// `rgba(255, 0, 127.5, 0.5)`, or `None` if it isn't a valid CSS color like that.
+    fn parse_css(css: &str) -> Option<Vec<f64>> {
+        let (channels, inner) = if let Some(inner) = css.strip_prefix("rgba(") {
+            (4, inner)
+        } else {
+            (3, css.strip_prefix("rgb(")?)
+        };
+        let values: Vec<f64> = inner
+            .strip_suffix(')')?
+            .split(", ")
+            .map(|value| value.parse().ok())
+            .collect::<Option<_>>()?;
+
+        let in_range = values.iter().enumerate().all(|(i, value)| {
+            let max = if i == 3 { 1.0 } else { 255.0 };
+            (0.0..=max).contains(value)
+        });
+        (values.len() == channels && in_range).then_some(values)
+    }
+
+    #[test]
+    fn lighten_stays_in_range() {
+        let colors = [
+            Color::white(),
+            Color::yellow(),
+            Color { r: 200.0 / 255.0, g: 220.0 / 255.0, b: 240.0 / 255.0, a: 1.0 },
+            Color::from_hsl(30.0, 1.0, 0.5),
+            Color::black(),
+        ];
+
+        for color in colors {
+            let lightened = color.lighten();
+            assert!(is_in_range(&lightened), "{:?} lightens to {:?}", color, lightened);
+            assert!(lightened.r >= color.r && lightened.g >= color.g && lightened.b >= color.b);
+            assert_eq!(lightened.a, color.a);
+
+            // lightening again can't go past white
+            assert!(is_in_range(&lightened.lighten().lighten()));
+        }
+    }
+
+    #[test]
+    fn display_writes_valid_css() {
+        assert_eq!(Color::white().to_string(), "rgb(255, 255, 255)");
+        assert_eq!(Color::red().with_alpha(0.5).to_string(), "rgba(255, 0, 0, 0.5)");
+
+        let colors = [Color::white(), Color::yellow(), Color::from_hsl(30.0, 1.0, 0.5)];
+        let lightened = colors.iter().map(Color::lighten);
+        for color in lightened.chain(Color::palette(7)) {
+            for color in [color, color.with_alpha(0.25)] {
+                let css = color.to_string();
+                assert!(parse_css(&css).is_some(), "{} is not a valid color", css);
+            }
+        }
+    }
+
+    #[test]
+    fn wrapping_distance_goes_across_the_nearest_edge() {
+        let at = |x, y| Position { x, y };
+
+        // across the left and right edge, and across the top and bottom edge
+        assert_eq!(at(0, 5).wrapping_distance(&at(9, 5), 10, 8), 1);
+        assert_eq!(at(9, 5).wrapping_distance(&at(0, 5), 10, 8), 1);
+        assert_eq!(at(4, 0).wrapping_distance(&at(4, 7), 10, 8), 1);
+        assert_eq!(at(4, 7).wrapping_distance(&at(4, 0), 10, 8), 1);
+
+        // across both edges at once, from one corner to the opposite one
+        assert_eq!(at(0, 0).wrapping_distance(&at(9, 7), 10, 8), 2);
+        assert_eq!(at(1, 6).wrapping_distance(&at(8, 1), 10, 8), 3 + 3);
+
+        // in the middle, going around is longer
+        assert_eq!(at(3, 3).wrapping_distance(&at(5, 4), 10, 8), 3);
+        assert_eq!(at(0, 0).wrapping_distance(&at(5, 4), 10, 8), 9);
+        assert_eq!(at(2, 2).wrapping_distance(&at(2, 2), 10, 8), 0);
+    }
+
+    #[test]
+    fn wrapping_distance_is_never_longer_than_manhattan_distance() {
+        let (width, height) = (7, 5);
+        let positions: Vec<Position> = (0..height)
+            .flat_map(|y| (0..width).map(move |x| Position { x, y }))
+            .collect();
+
+        for a in &positions {
+            for b in &positions {
+                let distance = a.wrapping_distance(b, width, height);
+                assert_eq!(distance, b.wrapping_distance(a, width, height));
+                assert!(distance <= a.manhattan_distance(b));
+                assert!(distance <= width / 2 + height / 2);
+            }
+        }
+    }
+
+    #[test]
+    fn every_key_steers_in_its_direction() {
+        let expected = [Direction::North, Direction::West, Direction::South, Direction::East];
+        let key_maps = [
+            (WASD_KEYS, ["w", "a", "s", "d"]),
+            (ARROW_KEYS, ["ArrowUp", "ArrowLeft", "ArrowDown", "ArrowRight"]),
+            (IJKL_KEYS, ["i", "j", "k", "l"]),
+            (TFGH_KEYS, ["t", "f", "g", "h"]),
+        ];
+
+        for (keys, names) in key_maps {
+            for (direction, name) in expected.into_iter().zip(names) {
+                assert_eq!(Direction::from_key(&keys, name), Some(direction), "{}", name);
+            }
+        }
+
+        assert_eq!(Direction::from_wasd("w"), Some(Direction::North));
+        assert_eq!(Direction::from_wasd("d"), Some(Direction::East));
+        assert_eq!(Direction::from_arrows("ArrowDown"), Some(Direction::South));
+        assert_eq!(Direction::from_arrows("ArrowLeft"), Some(Direction::West));
+    }
+
+    #[test]
+    fn letters_steer_in_either_case() {
+        assert_eq!(Direction::from_wasd("W"), Some(Direction::North));
+        assert_eq!(Direction::from_wasd("A"), Some(Direction::West));
+        assert_eq!(Direction::from_key(&IJKL_KEYS, "K"), Some(Direction::South));
+        assert_eq!(Direction::from_key(&TFGH_KEYS, "H"), Some(Direction::East));
+    }
+
+    #[test]
+    fn unknown_keys_steer_nowhere() {
+        for key in ["x", "", " ", "Enter", "Escape", "ww", "Arrow", "Up"] {
+            assert_eq!(Direction::from_wasd(key), None, "{:?}", key);
+            assert_eq!(Direction::from_arrows(key), None, "{:?}", key);
+        }
+
+        // the keys of one player don't steer another
+        assert_eq!(Direction::from_wasd("ArrowUp"), None);
+        assert_eq!(Direction::from_arrows("w"), None);
+        assert_eq!(Direction::from_key(&IJKL_KEYS, "w"), None);
+        assert_eq!(Direction::from_key(&TFGH_KEYS, "i"), None);
+    }
+}
@@ -0,0 +1,2165 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::{
+    bot::{self, BotDifficulty},
+    common::{Color, Direction, Palette, Position, TrailPattern},
+    rng::Rng,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Reverse,
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::{self, Debug, Display, Formatter},
+    sync::Arc,
+};
+
+/// The input scheme that steers a player.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Controller {
+    /// Keyboard, using the W, A, S and D keys unless they were rebound.
+    Wasd,
+    /// Keyboard, using the arrow keys unless they were rebound.
+    Arrows,
+    /// Keyboard, using the I, J, K and L keys unless they were rebound.
+    Ijkl,
+    /// Keyboard, using the T, F, G and H keys unless they were rebound.
+    Tfgh,
+    /// The gamepad with the specified index.
+    Gamepad(usize),
+    /// A computer-controlled player with the specified difficulty.
+    Bot(BotDifficulty),
+    /// A player on another client, in an online game.
+    Remote,
+}
+
+impl Display for Controller {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Controller::Wasd => write!(f, "WASD"),
+            Controller::Arrows => write!(f, "Arrows"),
+            Controller::Ijkl => write!(f, "IJKL"),
+            Controller::Tfgh => write!(f, "TFGH"),
+            Controller::Gamepad(index) => write!(f, "Gamepad {}", index + 1),
+            Controller::Bot(difficulty) => write!(f, "Bot ({})", difficulty),
+            Controller::Remote => write!(f, "Remote"),
+        }
+    }
+}
+
+impl Controller {
+    /// Whether this controller is one of the keyboard schemes.
+    pub fn is_keyboard(&self) -> bool {
+        matches!(
+            self,
+            Controller::Wasd | Controller::Arrows | Controller::Ijkl | Controller::Tfgh
+        )
+    }
+}
+
+/// What happens to the player that collects a pickup.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum PickupEffect {
+    /// The player moves two steps per turn for a while.
+    SpeedBoost,
+    /// The player scores a point.
+    ExtraPoint,
+    /// The oldest part of the player's trail disappears.
+    ShrinkTrail,
+    /// The next time the player runs into a wall or a trail, it passes through instead.
+    Ghost,
+}
+
+/// An item on the grid that a player can collect by moving onto it.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Pickup {
+    pub position: Position,
+    pub effect: PickupEffect,
+}
+
+/// The number of turns that a speed boost lasts.
+const SPEED_BOOST_TURNS: u32 = 10;
+
+/// The most boost that a player can store for holding the boost input. Every turn without boosting
+/// regains one, up to this maximum.
+pub const MAX_BOOST_CHARGE: u32 = 30;
+
+/// The boost that one turn at double speed uses up, while the boost input is held.
+const BOOST_COST: u32 = 3;
+
+/// The number of segments that a shrink pickup removes from a trail.
+const SHRINK_SEGMENTS: usize = 10;
+
+/// The number of steps between two new pickups.
+const PICKUP_INTERVAL_STEPS: u32 = 30;
+
+/// The closest that a new pickup can appear to the head of a player, so that nobody gets a pickup
+/// without going for it.
+const MIN_PICKUP_DISTANCE: usize = 3;
+
+/// The most pickups that can be on the grid at once.
+const MAX_PICKUPS: usize = 3;
+
+/// The number the countdown before each round starts at.
+pub const COUNTDOWN_START: u32 = 3;
+
+/// The shortest time between two steps when the game speeds up every round.
+const MIN_TICK_INTERVAL_MS: u32 = 50;
+
+/// A shrinking arena stops closing in before the open area inside it gets narrower than this
+/// many cells.
+const MIN_OPEN_SIZE: usize = 6;
+
+/// How far the players start from the border, in cells, in arenas that are large enough.
+const STARTING_INSET: usize = 4;
+
+/// The fewest players that a round can be played with.
+pub const MIN_PLAYERS: usize = 2;
+
+/// The number of ticks that the explosion after a collision lasts.
+pub const COLLISION_FRAMES: u32 = 6;
+
+/// The maximum number of directions that can be queued between two steps.
+const MAX_PENDING_DIRECTIONS: usize = 3;
+
+/// The most bots that a survival game grows to. After that, the bots get faster instead.
+pub const MAX_SURVIVAL_BOTS: usize = 5;
+
+/// After this many rounds in a row in which nobody scores, the game is over as a draw. Bots that
+/// never pick randomly can end every round in the same way, and would otherwise play forever.
+pub const MAX_DRAWN_ROUNDS: u32 = 10;
+
+/// The number of steps that a round lasts with the time limit on, after which the player with the
+/// most room left wins it. That is about a minute at normal speed.
+pub const ROUND_TIME_LIMIT_TICKS: u32 = 400;
+
+/// Controllers that are assigned to human players, in order.
+pub const HUMAN_CONTROLLERS: [Controller; 4] =
+    [Controller::Wasd, Controller::Arrows, Controller::Ijkl, Controller::Tfgh];
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Player {
+    pub color: Color,
+    /// The line that is drawn through the trail of this player.
+    pub pattern: TrailPattern,
+    pub score: u32,
+    pub segments: VecDeque<(Position, Direction)>,
+    pub controller: Controller,
+    /// The number of turns that this player still moves at double speed.
+    pub boost_turns: u32,
+    /// The boost that this player has left to use, by holding the boost input.
+    pub boost_charge: u32,
+    /// Whether the boost input of this player is held down.
+    pub boosting: bool,
+    /// The number of times that this player can still pass through a wall.
+    pub ghost_charges: u32,
+    /// The wall that this player is passing through, if its head is on one.
+    pub ghost_position: Option<Position>,
+    /// Directions that were entered since the last step, applied one per step.
+    pub pending_directions: VecDeque<Direction>,
+    /// Whether this player has crashed and is out for the rest of the round, while the others
+    /// play on. Its trail stays where it is.
+    #[serde(default)]
+    pub eliminated: bool,
+    /// The tick of the round in which this player was eliminated, which ranks the players that
+    /// are out.
+    #[serde(default)]
+    pub eliminated_at: u32,
+    /// The number of segments that were removed from the tail of the trail in this round, so the
+    /// cells that were left behind can be found even when the trail crosses itself.
+    #[serde(default)]
+    pub trimmed_segments: usize,
+}
+
+impl Player {
+    pub fn new(color: Color, position: Position, direction: Direction, controller: Controller) -> Self {
+        Player {
+            color,
+            pattern: TrailPattern::Solid,
+            score: 0,
+            segments: VecDeque::from(vec![(position, direction)]),
+            controller,
+            boost_turns: 0,
+            boost_charge: MAX_BOOST_CHARGE,
+            boosting: false,
+            ghost_charges: 0,
+            ghost_position: None,
+            pending_directions: VecDeque::new(),
+            eliminated: false,
+            eliminated_at: 0,
+            trimmed_segments: 0,
+        }
+    }
+
+    /// Queue a new direction for the head segment of the player. This function
+    /// is called by the input handling logic to set the direction of the
+    /// player. Each step applies one queued direction, so quick successive
+    /// turns are not lost. Directions that would reverse the player into its
+    /// own trail are rejected. Returns whether the direction was queued.
+    pub fn set_direction(&mut self, direction: Direction) -> bool {
+        let heading = match self.pending_directions.back() {
+            Some(pending) => Some(*pending),
+            None => self.segments.back().map(|(_, d)| *d),
+        };
+
+        if heading.is_some_and(|h| h == direction || h.opposite() == direction) {
+            return false;
+        }
+
+        if self.pending_directions.len() < MAX_PENDING_DIRECTIONS {
+            self.pending_directions.push_back(direction);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Apply the next queued direction to the head segment, if there is one.
+    fn apply_pending_direction(&mut self) {
+        if let (Some(direction), Some(head)) =
+            (self.pending_directions.pop_front(), self.segments.back_mut())
+        {
+            head.1 = direction;
+        }
+    }
+}
+
+/// Preset aspect ratios of the arena.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ArenaShape {
+    /// The shape of the original game.
+    #[default]
+    Classic,
+    Square,
+    Wide,
+}
+
+impl ArenaShape {
+    /// The width and height of a medium arena of this shape, in cells.
+    pub fn dimensions(&self) -> (usize, usize) {
+        match self {
+            ArenaShape::Classic => (32, 28),
+            ArenaShape::Square => (28, 28),
+            ArenaShape::Wide => (40, 24),
+        }
+    }
+
+    /// The next arena shape, wrapping around from the last to the first.
+    pub fn next(&self) -> Self {
+        match self {
+            ArenaShape::Classic => ArenaShape::Square,
+            ArenaShape::Square => ArenaShape::Wide,
+            ArenaShape::Wide => ArenaShape::Classic,
+        }
+    }
+}
+
+impl Display for ArenaShape {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ArenaShape::Classic => write!(f, "Classic"),
+            ArenaShape::Square => write!(f, "Square"),
+            ArenaShape::Wide => write!(f, "Wide"),
+        }
+    }
+}
+
+/// Preset arena sizes, which scale an arena shape without changing its aspect ratio.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ArenaSize {
+    Small,
+    Medium,
+    Large,
+}
+
+impl ArenaSize {
+    /// The width and height of an arena of this size and the specified shape, in cells.
+    pub fn dimensions(&self, shape: ArenaShape) -> (usize, usize) {
+        // the size scales the medium dimensions of the shape by 3/4, 4/4 or 5/4
+        let quarters = match self {
+            ArenaSize::Small => 3,
+            ArenaSize::Medium => 4,
+            ArenaSize::Large => 5,
+        };
+        let (width, height) = shape.dimensions();
+        (width * quarters / 4, height * quarters / 4)
+    }
+
+    /// The next arena size, wrapping around from largest to smallest.
+    pub fn next(&self) -> Self {
+        match self {
+            ArenaSize::Small => ArenaSize::Medium,
+            ArenaSize::Medium => ArenaSize::Large,
+            ArenaSize::Large => ArenaSize::Small,
+        }
+    }
+}
+
+impl Display for ArenaSize {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ArenaSize::Small => write!(f, "Small"),
+            ArenaSize::Medium => write!(f, "Medium"),
+            ArenaSize::Large => write!(f, "Large"),
+        }
+    }
+}
+
+/// Preset speeds at which the players move.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum GameSpeed {
+    Slow,
+    Normal,
+    Fast,
+}
+
+impl GameSpeed {
+    /// The time between two steps in milliseconds.
+    pub fn tick_interval_ms(&self) -> u32 {
+        match self {
+            GameSpeed::Slow => 200,
+            GameSpeed::Normal => 150,
+            GameSpeed::Fast => 100,
+        }
+    }
+
+    /// The next game speed, wrapping around from fastest to slowest.
+    pub fn next(&self) -> Self {
+        match self {
+            GameSpeed::Slow => GameSpeed::Normal,
+            GameSpeed::Normal => GameSpeed::Fast,
+            GameSpeed::Fast => GameSpeed::Slow,
+        }
+    }
+}
+
+impl Display for GameSpeed {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            GameSpeed::Slow => write!(f, "Slow"),
+            GameSpeed::Normal => write!(f, "Normal"),
+            GameSpeed::Fast => write!(f, "Fast"),
+        }
+    }
+}
+
+/// What happens at the edges of the arena.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ArenaMode {
+    /// The arena is surrounded by a wall, and players crash into it.
+    Walled,
+    /// There is no border: players that leave the arena on one side come back on the other.
+    Wrap,
+}
+
+impl ArenaMode {
+    /// The other arena mode.
+    pub fn next(&self) -> Self {
+        match self {
+            ArenaMode::Walled => ArenaMode::Wrap,
+            ArenaMode::Wrap => ArenaMode::Walled,
+        }
+    }
+}
+
+impl Display for ArenaMode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ArenaMode::Walled => write!(f, "Walled"),
+            ArenaMode::Wrap => write!(f, "Wrap"),
+        }
+    }
+}
+
+/// Obstacles inside the arena, on top of the border wall.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum ArenaLayout {
+    /// Only the border wall: the arena is empty inside.
+    Border,
+    /// A big plus sign in the middle of the arena.
+    Cross,
+    /// Small square blocks scattered over the arena.
+    Blocks,
+    /// Long walls that make a winding path through the arena.
+    Maze,
+}
+
+impl ArenaLayout {
+    /// The next arena layout, wrapping around from the last to the first.
+    pub fn next(&self) -> Self {
+        match self {
+            ArenaLayout::Border => ArenaLayout::Cross,
+            ArenaLayout::Cross => ArenaLayout::Blocks,
+            ArenaLayout::Blocks => ArenaLayout::Maze,
+            ArenaLayout::Maze => ArenaLayout::Border,
+        }
+    }
+}
+
+impl Display for ArenaLayout {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ArenaLayout::Border => write!(f, "Border"),
+            ArenaLayout::Cross => write!(f, "Cross"),
+            ArenaLayout::Blocks => write!(f, "Blocks"),
+            ArenaLayout::Maze => write!(f, "Maze"),
+        }
+    }
+}
+
+/// Which players move on each tick.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum MovementMode {
+    /// The players take turns: each tick moves one player.
+    RoundRobin,
+    /// Every tick moves all players at once.
+    Simultaneous,
+}
+
+impl MovementMode {
+    /// The other movement mode.
+    pub fn next(&self) -> Self {
+        match self {
+            MovementMode::RoundRobin => MovementMode::Simultaneous,
+            MovementMode::Simultaneous => MovementMode::RoundRobin,
+        }
+    }
+}
+
+impl Display for MovementMode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            MovementMode::RoundRobin => write!(f, "Round Robin"),
+            MovementMode::Simultaneous => write!(f, "Simultaneous"),
+        }
+    }
+}
+
+/// How points are awarded when players crash at the same time, such as in a head-on collision.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TieRule {
+    /// Nobody scores a point for the round.
+    NoPoints,
+    /// Both players count as crashed, so every player that is still in scores a point for each of
+    /// them, but they don't score for each other.
+    BothLose,
+}
+
+impl TieRule {
+    /// The other tie rule.
+    pub fn next(&self) -> Self {
+        match self {
+            TieRule::NoPoints => TieRule::BothLose,
+            TieRule::BothLose => TieRule::NoPoints,
+        }
+    }
+}
+
+impl Display for TieRule {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            TieRule::NoPoints => write!(f, "No Points"),
+            TieRule::BothLose => write!(f, "Both Lose"),
+        }
+    }
+}
+
+/// What the game is doing. Every tick either stays in the phase or moves on to the next one:
+///
+/// ```text
+///                 no crash
+///                 ┌──────┐
+///                 v      │   crash                   game over
+/// Countdown ───> Step ───┴───────> Collision ───┬──────────────> GameOver
+///     ^           ^ │                           │
+///     │           │ v pause                     │ next round
+///     │         Paused                          v
+///     └───────────────────────────────────── Score
+/// ```
+///
+/// Some phases last a fixed number of ticks, which [`Phase::duration`] gives, and the others
+/// last until something happens. How long a tick takes depends on the phase as well, which
+/// [`GameState::tick_interval`] gives.
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum Phase {
+    /// The players wait for the countdown before a round.
+    Countdown,
+    /// The players move, until all but one of them have crashed.
+    Step,
+    /// Nothing happens, until the game is resumed.
+    Paused,
+    /// The crashed players explode.
+    Collision,
+    /// The scores are shown between rounds.
+    Score,
+    /// Nothing happens anymore.
+    GameOver,
+}
+
+impl Phase {
+    /// The number of ticks after which the phase moves on by itself, or `None` for the phases
+    /// that last until something happens.
+    pub fn duration(&self) -> Option<u32> {
+        match self {
+            Phase::Countdown => Some(COUNTDOWN_START + 1),
+            Phase::Collision => Some(COLLISION_FRAMES),
+            Phase::Score => Some(1),
+            Phase::Step | Phase::Paused | Phase::GameOver => None,
+        }
+    }
+}
+
+/// A recording of a match, made up of the direction of every step and the player that made it.
+/// Together with the initial state of the match, this is enough to replay the match exactly.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Recording {
+    pub moves: Vec<(usize, Direction)>,
+    /// For every turn of a player without a speed boost from a pickup, whether the player held
+    /// the boost input.
+    pub boosts: Vec<bool>,
+}
+
+impl Recording {
+    /// Prepare the specified initial state to replay this recording. Every step of the returned
+    /// game follows the recorded moves instead of the players' input.
+    pub fn playback(&self, initial: GameState) -> GameState {
+        let mut game_state = initial;
+        game_state.replay = Some(self.moves.iter().copied().collect());
+        game_state.replay_boosts = self.boosts.iter().copied().collect();
+        game_state
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct GameState {
+    pub phase: Phase,
+    pub grid_width: usize,
+    pub grid_height: usize,
+    pub arena_mode: ArenaMode,
+    pub arena_layout: ArenaLayout,
+    pub active_player: usize,
+    pub players: Vec<Player>,
+    pub obstacles: Vec<Position>,
+    pub max_score: u32,
+    /// The colors of the obstacles and crashes, and of the bots that join a survival game.
+    pub palette: Palette,
+    /// How points are awarded after a head-on collision.
+    pub tie_rule: TieRule,
+    /// Whether the players take turns or move at the same time.
+    pub movement_mode: MovementMode,
+    /// The number of segments after which a trail starts to disappear from the back, if any.
+    pub max_trail_length: Option<usize>,
+    /// The number of segments that every trail starts a round with, including the head.
+    pub start_length: usize,
+    /// Time between two steps in milliseconds.
+    pub tick_interval_ms: u32,
+    /// Time that the score is shown between rounds in milliseconds.
+    pub score_interval_ms: u32,
+    /// Time between two numbers of the countdown in milliseconds.
+    pub countdown_interval_ms: u32,
+    /// The number of ticks since the current phase started.
+    #[serde(default)]
+    pub elapsed: u32,
+    /// Whether the players move a little faster every round.
+    pub speed_up: bool,
+    /// Whether this is a survival game, in which a single human faces more bots every round and
+    /// the game is over as soon as the human crashes. The human scores a point for every round
+    /// they survive.
+    pub survival: bool,
+    /// Whether this is a practice game, in which a single player drives around without scoring.
+    /// The game is never over: a crash only puts the player back at the start.
+    #[serde(default)]
+    pub practice: bool,
+    /// The moves made so far in this game.
+    pub recording: Recording,
+    /// The moves that are still to be replayed, if this game is a replay.
+    pub replay: Option<VecDeque<(usize, Direction)>>,
+    /// The boosts that are still to be replayed, if this game is a replay.
+    pub replay_boosts: VecDeque<bool>,
+    /// The source of randomness for bots.
+    pub rng: Rng,
+    /// The seed that all randomness in the match comes from, so that the same seed, with the same
+    /// input, gives the same match.
+    pub seed: u64,
+    /// Whether pickups appear on the grid.
+    pub pickups_enabled: bool,
+    /// The pickups that are on the grid.
+    pub pickups: Vec<Pickup>,
+    /// The number of steps until the next pickup appears.
+    pub steps_until_pickup: u32,
+    /// The number of steps between two rings of obstacles that close in on the arena from the
+    /// border, or `None` if the arena keeps its size. The rings open up again every round.
+    #[serde(default)]
+    pub shrink_interval: Option<u32>,
+    /// The number of steps until the next ring closes in.
+    #[serde(default)]
+    pub steps_until_shrink: u32,
+    /// The number of rings, counted from the border inwards, that have closed in this round.
+    #[serde(default)]
+    pub closed_rings: usize,
+    /// The number of obstacles at the end of `obstacles` that belong to the closed rings.
+    #[serde(default)]
+    pub closed_cells: usize,
+    /// The number of ticks after which a round without a crash is over, or `None` if a round
+    /// lasts until somebody crashes. The player with the most room left wins the round.
+    #[serde(default)]
+    pub max_round_ticks: Option<u32>,
+    /// The number of ticks that the players have been moving this round, without the countdown
+    /// and the pauses.
+    #[serde(default)]
+    pub round_ticks: u32,
+    /// The points of all players together when this round started, to tell whether anybody
+    /// scored in it.
+    #[serde(default)]
+    pub round_start_points: u32,
+    /// The number of rounds in a row that ended without anybody scoring.
+    #[serde(default)]
+    pub drawn_rounds: u32,
+    /// The source of randomness for pickups. This is separate from the bots, so that replays,
+    /// in which the bots don't decide anything, get the same pickups.
+    pub pickup_rng: Rng,
+    /// The most segments that a single trail has had in this game.
+    pub longest_trail: usize,
+    /// For every cell, how many obstacles and segments are on it, so that collisions can be
+    /// checked without going over every trail. Trails can overlap, for example when a player
+    /// passes through a wall, so cells are counted instead of just marked.
+    #[serde(skip)]
+    occupied: HashMap<Position, u32>,
+    /// The cells with an obstacle, which don't change during a game. They are shared between
+    /// copies of the game, like the ones that the bots look ahead with.
+    #[serde(skip)]
+    obstacle_cells: Arc<HashSet<Position>>,
+    /// Whether the bots explain their decisions in `bot_decisions`, for debugging.
+    #[serde(skip)]
+    pub explain_bots: bool,
+    /// The last decision of every bot this round, by the index of the player, if the bots explain
+    /// their decisions.
+    #[serde(skip)]
+    pub bot_decisions: HashMap<usize, bot::Decision>,
+}
+
+impl GameState {
+    /// Create a new game with `num_players` players, where the first `num_humans`
+    /// players are controlled by humans and the remaining players are controlled
+    /// by bots of the specified difficulty. The arena is `width` by `height` cells.
+    /// Players get the specified colors in order; if there are more players than
+    /// colors, the remaining players get distinct colors from [`Color::palette`].
+    pub fn new(
+        num_humans: usize,
+        num_players: usize,
+        bot_difficulty: BotDifficulty,
+        max_score: u32,
+        width: usize,
+        height: usize,
+        colors: &[Color],
+    ) -> Self {
+        let palette = Color::palette(num_players);
+        let players = (0..num_players)
+            .map(|i| {
+                let controller = match HUMAN_CONTROLLERS.get(i) {
+                    Some(controller) if i < num_humans => controller.clone(),
+                    _ => Controller::Bot(bot_difficulty),
+                };
+                let (position, direction) = starting_state(i, num_players, width, height);
+                let color = colors.get(i).copied().unwrap_or(palette[i]);
+                Player::new(color, position, direction, controller)
+            })
+            .collect();
+
+        let mut game_state = GameState {
+            phase: Phase::Countdown,
+            active_player: 0,
+            players,
+            max_score,
+            palette: Palette::Standard,
+            tie_rule: TieRule::NoPoints,
+            movement_mode: MovementMode::RoundRobin,
+            max_trail_length: None,
+            start_length: 1,
+            grid_width: width,
+            grid_height: height,
+            arena_mode: ArenaMode::Walled,
+            arena_layout: ArenaLayout::Border,
+            obstacles: generate_obstacles(ArenaLayout::Border, width, height),
+            tick_interval_ms: GameSpeed::Normal.tick_interval_ms(),
+            score_interval_ms: 2000,
+            countdown_interval_ms: 600,
+            elapsed: 0,
+            speed_up: false,
+            survival: false,
+            practice: false,
+            recording: Recording::default(),
+            replay: None,
+            replay_boosts: VecDeque::new(),
+            rng: Rng::seeded(0),
+            seed: 0,
+            pickups_enabled: false,
+            pickups: vec![],
+            steps_until_pickup: PICKUP_INTERVAL_STEPS,
+            shrink_interval: None,
+            steps_until_shrink: 0,
+            closed_rings: 0,
+            closed_cells: 0,
+            max_round_ticks: None,
+            round_ticks: 0,
+            round_start_points: 0,
+            drawn_rounds: 0,
+            pickup_rng: Rng::seeded(0),
+            longest_trail: 0,
+            occupied: HashMap::new(),
+            obstacle_cells: Arc::default(),
+            explain_bots: false,
+            bot_decisions: HashMap::new(),
+        };
+        game_state.set_seed(Rng::from_entropy().next_u64());
+        game_state.index_obstacles();
+        game_state.count_occupied();
+        game_state
+    }
+
+    /// Start all randomness of the match over from the specified seed.
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.rng = Rng::seeded(seed);
+        // pickups get their own numbers, so that they don't repeat the choices of the bots
+        self.pickup_rng = Rng::seeded(Rng::seeded(seed).next_u64());
+    }
+
+    /// Advance the game one tick. While stepping, the active player moves in its direction, or
+    /// every player at once in simultaneous mode. A player that hits a wall is eliminated and
+    /// the players that are still in score a point. If two heads meet, the tie rule decides who
+    /// scores. Once a player reaches the winning score, or after `MAX_DRAWN_ROUNDS` rounds in a row
+    /// without a point, the game is over. In the other phases, a
+    /// tick advances the countdown, the explosion and the score screen.
+    pub fn tick(&mut self) {
+        self.elapsed = self.elapsed.saturating_add(1);
+        let is_done = self.phase.duration().is_some_and(|duration| self.elapsed >= duration);
+
+        match self.phase {
+            Phase::Countdown => {
+                // while counting down, the players are frozen until the countdown is done, and
+                // without enough players, the game is over before it started
+                if is_done && self.has_enough_players() {
+                    self.set_phase(Phase::Step);
+                } else if is_done {
+                    self.set_phase(Phase::GameOver);
+                }
+            }
+            Phase::Step if !self.has_enough_players() => self.set_phase(Phase::GameOver),
+            Phase::Step => {
+                // while we are stepping, a tick progresses player movement and
+                // calculates the consequence
+                self.round_ticks = self.round_ticks.saturating_add(1);
+                match self.movement_mode {
+                    MovementMode::RoundRobin => self.take_turn(),
+                    MovementMode::Simultaneous => self.step_all(),
+                }
+                self.eliminate_crashed();
+
+                if !self.has_collision() {
+                    if self.movement_mode == MovementMode::RoundRobin {
+                        self.set_next_player();
+                    }
+                    self.spawn_pickups();
+                    self.close_in();
+
+                    // a player that a closing ring lands on crashes too
+                    self.eliminate_crashed();
+                }
+
+                // the round is over when the last players crash
+                if self.has_collision() {
+                    if !self.practice {
+                        self.score();
+                        self.count_drawn_round();
+                    }
+                    self.set_phase(Phase::Collision);
+                } else if self.is_out_of_time() {
+                    self.score_by_room();
+                    self.count_drawn_round();
+                    self.set_phase(if self.is_game_over() { Phase::GameOver } else { Phase::Score });
+                }
+            }
+            Phase::Collision => {
+                // while colliding, ticks advance the explosion animation, after
+                // which the score is shown, or in practice, the player drives on from the start
+                if is_done && self.practice {
+                    self.reset_practice();
+                } else if is_done {
+                    self.set_phase(if self.is_game_over() { Phase::GameOver } else { Phase::Score });
+                }
+            }
+            Phase::Score => {
+                // while scoring, the last tick resets the players, allowing for
+                // an animation in between
+                if is_done {
+                    let max_bots = self.survival && !self.add_survival_bot();
+                    self.reset_players();
+
+                    if self.speed_up || max_bots {
+                        self.tick_interval_ms =
+                            (self.tick_interval_ms * 9 / 10).max(MIN_TICK_INTERVAL_MS);
+                    }
+
+                    self.set_phase(Phase::Countdown);
+                }
+            }
+            Phase::Paused | Phase::GameOver => {
+                // while the game is paused or over, ticks do nothing
+            }
+        }
+    }
+
+    /// Advance the game `n` ticks at once, like to fast-forward or to play a game without drawing
+    /// it. The bots decide with the seeded randomness of the game, so the same state always ends
+    /// up the same. Stops early when ticks don't change anything anymore, like after the game is
+    /// over.
+    pub fn tick_n(&mut self, n: usize) {
+        for _ in 0..n {
+            if self.tick_interval().is_none() {
+                break;
+            }
+            self.tick();
+        }
+    }
+
+    /// Whether there are enough players to play a round, which is at least `MIN_PLAYERS`, or a
+    /// single player in practice.
+    pub fn has_enough_players(&self) -> bool {
+        self.players.len() >= if self.practice { 1 } else { MIN_PLAYERS }
+    }
+
+    /// Clear the trails in a practice game and put the players back at the start, where they
+    /// drive on right away. Does nothing in other games.
+    pub fn reset_practice(&mut self) {
+        if self.practice {
+            self.reset_players();
+            self.set_phase(Phase::Step);
+        }
+    }
+
+    /// The player whose turn it is, if there are any players at all.
+    pub fn active(&self) -> Option<&Player> {
+        self.players.get(self.active_player)
+    }
+
+    /// Move on to the specified phase, which starts counting its ticks from zero.
+    pub fn set_phase(&mut self, phase: Phase) {
+        self.phase = phase;
+        self.elapsed = 0;
+    }
+
+    /// The time between two ticks in the current phase in milliseconds, or `None` if ticking
+    /// doesn't change anything, like while the game is paused or over.
+    pub fn tick_interval(&self) -> Option<u32> {
+        match self.phase {
+            Phase::Countdown => Some(self.countdown_interval_ms),
+            Phase::Step | Phase::Collision => Some(self.tick_interval_ms),
+            Phase::Score => Some(self.score_interval_ms),
+            Phase::Paused | Phase::GameOver => None,
+        }
+    }
+
+    /// The number that the countdown is at, where 0 means "go".
+    pub fn countdown(&self) -> u32 {
+        match self.phase {
+            Phase::Countdown => COUNTDOWN_START.saturating_sub(self.elapsed),
+            _ => 0,
+        }
+    }
+
+    /// Serialize the game to JSON, so it can be saved and restored later.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("game state should be serializable")
+    }
+
+    /// Restore a game that was serialized with [`GameState::to_json`].
+    pub fn from_json(s: &str) -> Result<Self, serde_json::Error> {
+        let mut game_state: GameState = serde_json::from_str(s)?;
+        game_state.index_obstacles();
+        game_state.count_occupied();
+        Ok(game_state)
+    }
+
+    /// Change the number of segments that the trails start with, and put the players back at the
+    /// start of the round with their new trails.
+    pub fn set_start_length(&mut self, start_length: usize) {
+        self.start_length = start_length.max(1);
+        self.reset_players();
+    }
+
+    /// Let the arena close in by another ring every `interval` steps, or keep its size with
+    /// `None`.
+    pub fn set_shrink_interval(&mut self, interval: Option<u32>) {
+        self.shrink_interval = interval;
+        self.steps_until_shrink = interval.unwrap_or(0);
+    }
+
+    /// Change what happens at the edges of the arena and which obstacles are inside it. This
+    /// replaces the obstacles, so it should be done before the game starts. In a wrap-around
+    /// arena, the border wall is left out.
+    pub fn set_arena(&mut self, arena_mode: ArenaMode, arena_layout: ArenaLayout) {
+        let obstacles = generate_obstacles(arena_layout, self.grid_width, self.grid_height);
+
+        self.arena_mode = arena_mode;
+        self.arena_layout = arena_layout;
+        self.closed_rings = 0;
+        self.closed_cells = 0;
+        self.obstacles = match arena_mode {
+            ArenaMode::Walled => obstacles,
+            ArenaMode::Wrap => obstacles.into_iter().filter(|p| !self.is_on_border(p)).collect(),
+        };
+        self.index_obstacles();
+        self.count_occupied();
+    }
+
+    /// Determine where a player at the specified position ends up after moving in the specified
+    /// direction. In a walled arena, this returns `None` when the player would leave the grid.
+    pub fn next_position(&self, position: &Position, direction: &Direction) -> Option<Position> {
+        match self.arena_mode {
+            ArenaMode::Walled => position.next_within(direction, self.grid_width, self.grid_height),
+            ArenaMode::Wrap => Some(position.next(direction, self.grid_width, self.grid_height)),
+        }
+    }
+
+    /// Let the first human player that is still using the keyboard switch to the gamepad with the
+    /// specified index. Nothing changes if another player already uses that gamepad, or if all
+    /// human players already use a gamepad.
+    pub fn connect_gamepad(&mut self, index: usize) {
+        let in_use = self
+            .players
+            .iter()
+            .any(|p| matches!(p.controller, Controller::Gamepad(i) if i == index));
+
+        if in_use {
+            return;
+        }
+
+        let keyboard_player = self.players.iter_mut().find(|p| p.controller.is_keyboard());
+
+        if let Some(player) = keyboard_player {
+            player.controller = Controller::Gamepad(index);
+        }
+    }
+
+    /// Let the player that uses the gamepad with the specified index switch back to the keyboard.
+    pub fn disconnect_gamepad(&mut self, index: usize) {
+        for (i, player) in self.players.iter_mut().enumerate() {
+            if matches!(player.controller, Controller::Gamepad(g) if g == index) {
+                player.controller = HUMAN_CONTROLLERS.get(i).cloned().unwrap_or(Controller::Wasd);
+            }
+        }
+    }
+
+    /// The number of steps between two positions, following the edges of the arena the same way
+    /// that players do. Obstacles are not taken into account.
+    pub fn distance(&self, a: &Position, b: &Position) -> usize {
+        match self.arena_mode {
+            ArenaMode::Walled => a.manhattan_distance(b),
+            ArenaMode::Wrap => a.wrapping_distance(b, self.grid_width, self.grid_height),
+        }
+    }
+
+    /// Pause the game while the players are moving, or resume a paused game. In other phases,
+    /// this does nothing.
+    pub fn toggle_pause(&mut self) {
+        match self.phase {
+            Phase::Step => self.set_phase(Phase::Paused),
+            Phase::Paused => self.set_phase(Phase::Step),
+            _ => {}
+        }
+    }
+
+    /// Decide the direction of the active player, from the recording if this game is a replay, or
+    /// otherwise from the bot that controls the player, if any.
+    fn apply_move(&mut self) {
+        if self.replay.is_some() {
+            self.apply_replay_move();
+        } else {
+            self.apply_bot_move();
+        }
+    }
+
+    /// Move the active player one step, or two steps while it is boosted.
+    fn take_turn(&mut self) {
+        let boosted = self.use_boost(self.active_player);
+
+        self.move_active_player();
+
+        if boosted && !self.has_collision() {
+            self.move_active_player();
+        }
+    }
+
+    /// Move every player one step, and the players that are boosted another step after that.
+    fn step_all(&mut self) {
+        let everyone: Vec<usize> =
+            (0..self.players.len()).filter(|i| !self.players[*i].eliminated).collect();
+        let boosted: Vec<usize> = everyone.iter().copied().filter(|i| self.use_boost(*i)).collect();
+
+        self.step_players(&everyone);
+
+        if !boosted.is_empty() && !self.has_collision() {
+            self.step_players(&boosted);
+        }
+
+        self.active_player = 0;
+    }
+
+    /// Move the specified players one step at the same time. All of them decide on their
+    /// direction before anyone moves, so that no bot can react to the move that another player
+    /// makes in the same tick.
+    fn step_players(&mut self, players: &[usize]) {
+        for i in players {
+            self.active_player = *i;
+            self.apply_move();
+        }
+
+        for i in players {
+            self.active_player = *i;
+            self.step();
+            self.record_move();
+            self.pass_through_wall();
+            self.collect_pickup();
+        }
+    }
+
+    /// Let the active player decide on a direction and move one step.
+    fn move_active_player(&mut self) {
+        self.apply_move();
+        self.step();
+        self.record_move();
+        self.pass_through_wall();
+        self.collect_pickup();
+    }
+
+    /// Decide whether the specified player moves at double speed this turn, which it does while it
+    /// has a speed boost from a pickup, or while it holds the boost input and has enough boost left.
+    /// Returns whether the player is boosted for this turn.
+    fn use_boost(&mut self, index: usize) -> bool {
+        let player = &mut self.players[index];
+
+        if player.boost_turns > 0 {
+            player.boost_turns -= 1;
+            return true;
+        }
+
+        let boosted = match self.replay {
+            Some(_) => self.replay_boosts.pop_front().unwrap_or(false),
+            None => player.boosting && player.boost_charge >= BOOST_COST,
+        };
+        self.recording.boosts.push(boosted);
+
+        if boosted {
+            player.boost_charge = player.boost_charge.saturating_sub(BOOST_COST);
+        } else {
+            player.boost_charge = (player.boost_charge + 1).min(MAX_BOOST_CHARGE);
+        }
+
+        boosted
+    }
+
+    /// Let the active player collect the pickup that its head is on, if any.
+    fn collect_pickup(&mut self) {
+        let player = &mut self.players[self.active_player];
+        let Some((head, _)) = player.segments.back() else {
+            return;
+        };
+        let Some(index) = self.pickups.iter().position(|p| p.position == *head) else {
+            return;
+        };
+
+        match self.pickups.remove(index).effect {
+            PickupEffect::SpeedBoost => player.boost_turns = SPEED_BOOST_TURNS,
+            PickupEffect::ExtraPoint => player.score += 1,
+            PickupEffect::ShrinkTrail => {
+                let removed = SHRINK_SEGMENTS.min(player.segments.len() - 1);
+                for (position, _) in player.segments.drain(..removed) {
+                    vacate(&mut self.occupied, &position);
+                }
+                player.trimmed_segments += removed;
+            }
+            PickupEffect::Ghost => player.ghost_charges += 1,
+        }
+    }
+
+    /// Count down to the next pickup, and place it on a random empty cell when it is time.
+    fn spawn_pickups(&mut self) {
+        if !self.pickups_enabled || self.pickups.len() >= MAX_PICKUPS {
+            return;
+        }
+
+        self.steps_until_pickup = self.steps_until_pickup.saturating_sub(1);
+        if self.steps_until_pickup > 0 {
+            return;
+        }
+        self.steps_until_pickup = PICKUP_INTERVAL_STEPS;
+
+        // try a few random cells, since the grid is mostly empty
+        for _ in 0..10 {
+            let position = Position {
+                x: self.pickup_rng.below(self.grid_width),
+                y: self.pickup_rng.below(self.grid_height),
+            };
+
+            let is_near_head = self.players.iter().any(|player| {
+                player
+                    .segments
+                    .back()
+                    .is_some_and(|(head, _)| self.distance(head, &position) < MIN_PICKUP_DISTANCE)
+            });
+            let is_taken = self.occupied.contains_key(&position)
+                || self.pickups.iter().any(|p| p.position == position);
+
+            if !is_taken && !is_near_head {
+                let effect = match self.pickup_rng.below(4) {
+                    0 => PickupEffect::SpeedBoost,
+                    1 => PickupEffect::ExtraPoint,
+                    2 => PickupEffect::ShrinkTrail,
+                    _ => PickupEffect::Ghost,
+                };
+                self.pickups.push(Pickup { position, effect });
+                return;
+            }
+        }
+    }
+
+    /// Let the bot decide the direction of the active player, if that player is
+    /// controlled by a bot. Only the player that is about to move gets to decide,
+    /// so that the bot sees the most recent state of the board.
+    fn apply_bot_move(&mut self) {
+        if let Some(Controller::Bot(difficulty)) = self.active().map(|p| p.controller.clone()) {
+            let mut rng = self.rng.clone();
+            let new_direction = if self.explain_bots {
+                let decision = bot::decide(self, difficulty, &mut rng);
+                let direction = decision.direction;
+                self.bot_decisions.insert(self.active_player, decision);
+                direction
+            } else {
+                bot::next_direction(self, difficulty, &mut rng)
+            };
+            self.players[self.active_player].set_direction(new_direction);
+            self.rng = rng;
+        }
+    }
+
+    /// Override the direction of the active player with the next recorded move.
+    fn apply_replay_move(&mut self) {
+        let Some((player, direction)) = self.replay.as_mut().and_then(|r| r.pop_front()) else {
+            return;
+        };
+
+        let player = &mut self.players[player];
+        player.pending_directions.clear();
+        if let Some(head) = player.segments.back_mut() {
+            head.1 = direction;
+        }
+    }
+
+    /// Append the direction in which the active player just moved to the recording.
+    fn record_move(&mut self) {
+        if let Some((_, direction)) = self.active().and_then(|p| p.segments.back()) {
+            self.recording.moves.push((self.active_player, *direction));
+        }
+    }
+
+    /// Move the active player one step in the specified direction, to look ahead at what would
+    /// happen. This doesn't change the phase, the scores or whose turn it is. It does everything
+    /// else that a step does to the active player: its queued directions are dropped, its head
+    /// advances, its trail is cut to the maximum trail length, and the occupied cells and the
+    /// longest trail are updated to match. Bots call this on a clone of the game state.
+    pub fn simulate_step(&mut self, direction: Direction) {
+        let player = &mut self.players[self.active_player];
+        player.pending_directions.clear();
+        if let Some(head) = player.segments.back_mut() {
+            head.1 = direction;
+        }
+
+        self.step();
+    }
+
+    /// Advance the game one step, by moving the active player in its direction. A player that
+    /// would leave a walled arena stays in place instead, which collides with its own trail. If the
+    /// trail gets longer than the maximum trail length, its oldest segments are removed.
+    fn step(&mut self) {
+        self.players[self.active_player].apply_pending_direction();
+
+        let (new_position, direction) = {
+            let (position, direction) = self.players[self.active_player]
+                .segments
+                .back()
+                .unwrap_or_else(|| panic!("Player {} has no segments", self.active_player));
+
+            (
+                self.next_position(position, direction).unwrap_or(*position),
+                *direction,
+            )
+        };
+
+        let player = &mut self.players[self.active_player];
+        player.segments.push_back((new_position, direction));
+        *self.occupied.entry(new_position).or_default() += 1;
+
+        if let Some(max_trail_length) = self.max_trail_length {
+            while player.segments.len() > max_trail_length.max(1) {
+                if let Some((position, _)) = player.segments.pop_front() {
+                    vacate(&mut self.occupied, &position);
+                }
+                player.trimmed_segments += 1;
+            }
+        }
+
+        self.longest_trail = self.longest_trail.max(player.segments.len());
+    }
+
+    /// Check whether any player has collided with a wall or another player.
+    pub fn has_collision(&self) -> bool {
+        !self.crashed_players().is_empty()
+    }
+
+    /// Check whether the specified player has collided with a wall or another player. Moving onto
+    /// the head of another player is a head-on collision, which is a crash for both players.
+    fn player_has_collision(&self, index: usize) -> bool {
+        let player = &self.players[index];
+        let (position, _) = player
+            .segments
+            .back()
+            .unwrap_or_else(|| panic!("Player {} has no segments", index));
+
+        // a player that is passing through a wall can still run into another player head-on
+        let is_ghosting = player.ghost_position == Some(*position);
+
+        self.hits_head(index) || (!is_ghosting && self.hits_wall(index))
+    }
+
+    /// Check whether the head of the specified player is on an obstacle or a trail.
+    fn hits_wall(&self, index: usize) -> bool {
+        let (position, _) = self.players[index].segments.back().unwrap();
+
+        // the cell is counted once for every head on it, including the player's own head
+        let occupied = self.occupied.get(position).copied().unwrap_or(0);
+        let heads = self
+            .players
+            .iter()
+            .filter(|player| player.segments.back().is_some_and(|(p, _)| p == position))
+            .count() as u32;
+
+        occupied > heads
+    }
+
+    /// Check whether an obstacle or a segment of any player is on the specified cell.
+    pub fn is_occupied(&self, position: &Position) -> bool {
+        self.occupied.contains_key(position)
+    }
+
+    /// The number of cells that hold an obstacle or a segment.
+    pub fn occupied_cells(&self) -> usize {
+        self.occupied.len()
+    }
+
+    /// Whether there is an obstacle on the specified cell.
+    pub fn is_obstacle(&self, position: &Position) -> bool {
+        self.obstacle_cells.contains(position)
+    }
+
+    /// The cells with an obstacle.
+    pub fn obstacle_cells(&self) -> &HashSet<Position> {
+        &self.obstacle_cells
+    }
+
+    /// Look up the cells of the obstacles again, after they changed.
+    fn index_obstacles(&mut self) {
+        self.obstacle_cells = Arc::new(self.obstacles.iter().copied().collect());
+    }
+
+    /// Count the obstacles and segments on every cell from scratch, which is also needed after
+    /// changing the trails directly.
+    pub(crate) fn count_occupied(&mut self) {
+        self.occupied.clear();
+
+        let segments = self.players.iter().flat_map(|player| player.segments.iter());
+        for position in self.obstacles.iter().chain(segments.map(|(p, _)| p)) {
+            *self.occupied.entry(*position).or_default() += 1;
+        }
+    }
+
+    /// Check whether the head of the specified player is on the head of another player.
+    fn hits_head(&self, index: usize) -> bool {
+        let (position, _) = self.players[index].segments.back().unwrap();
+
+        self.players.iter().enumerate().any(|(i, player)| {
+            i != index && player.segments.back().is_some_and(|(p, _)| p == position)
+        })
+    }
+
+    /// Let the active player pass through the wall that it just moved onto, if it has a ghost
+    /// charge left. The border of a walled arena can't be passed.
+    fn pass_through_wall(&mut self) {
+        let index = self.active_player;
+        let (position, _) = *self.players[index].segments.back().unwrap();
+        let is_passable = self.arena_mode == ArenaMode::Wrap || !self.is_on_border(&position);
+
+        if self.players[index].ghost_charges > 0 && is_passable && self.hits_wall(index) {
+            let player = &mut self.players[index];
+            player.ghost_charges -= 1;
+            player.ghost_position = Some(position);
+        }
+    }
+
+    /// Check whether a position is on the outermost ring of cells of the arena.
+    fn is_on_border(&self, position: &Position) -> bool {
+        position.x == 0
+            || position.y == 0
+            || position.x == self.grid_width - 1
+            || position.y == self.grid_height - 1
+    }
+
+    /// The players that have crashed, by index. When the players take turns, this is the active
+    /// player and possibly the player it ran into head-on. When they move simultaneously, any
+    /// number of players can crash at once. The players that were eliminated earlier in the round
+    /// don't count.
+    pub fn crashed_players(&self) -> Vec<usize> {
+        (0..self.players.len())
+            .filter(|i| !self.players[*i].eliminated && self.player_has_collision(*i))
+            .collect()
+    }
+
+    /// The players that are still in the round, by index.
+    pub fn remaining_players(&self) -> Vec<usize> {
+        (0..self.players.len())
+            .filter(|i| !self.players[*i].eliminated)
+            .collect()
+    }
+
+    /// Take the players that have crashed out of the round, and let the others play on, as long
+    /// as at least two of them are left. The players that stay in score for outlasting the ones
+    /// that are out. When fewer players would be left, the crashed players stay in, so that the
+    /// round ends with their crash. Survival and practice games don't eliminate anyone.
+    fn eliminate_crashed(&mut self) {
+        if self.survival || self.practice {
+            return;
+        }
+
+        let crashed = self.crashed_players();
+        if crashed.is_empty() || self.remaining_players().len() - crashed.len() < MIN_PLAYERS {
+            return;
+        }
+
+        self.score();
+        for i in crashed {
+            self.players[i].eliminated = true;
+            self.players[i].eliminated_at = self.round_ticks;
+        }
+    }
+
+    /// The players from first to last place in the round so far, by index: the players that are
+    /// still in, then the players that crashed at the end of the round, and then the players that
+    /// were eliminated, from last to first out. Players that went out at the same time keep their
+    /// order.
+    pub fn placements(&self) -> Vec<usize> {
+        let crashed = self.crashed_players();
+        let mut placements: Vec<usize> = (0..self.players.len()).collect();
+        placements.sort_by_key(|i| {
+            let player = &self.players[*i];
+            let out_at = if player.eliminated {
+                Some(player.eliminated_at)
+            } else if crashed.contains(i) {
+                Some(self.round_ticks)
+            } else {
+                None
+            };
+            (out_at.is_some(), Reverse(out_at))
+        });
+        placements
+    }
+
+    /// Close the next ring of a shrinking arena in, once it is time. Rings that are taken by
+    /// obstacles already, like the border of a walled arena, are skipped. The pickups under the
+    /// ring are gone, and a player whose head is on it has crashed.
+    fn close_in(&mut self) {
+        let Some(interval) = self.shrink_interval else {
+            return;
+        };
+
+        self.steps_until_shrink = self.steps_until_shrink.saturating_sub(1);
+        if self.steps_until_shrink > 0 {
+            return;
+        }
+        self.steps_until_shrink = interval;
+
+        let (width, height) = (self.grid_width, self.grid_height);
+        while width.min(height) >= 2 * (self.closed_rings + 1) + MIN_OPEN_SIZE {
+            let ring = self.closed_rings;
+            let (right, bottom) = (width - 1 - ring, height - 1 - ring);
+            let cells: Vec<Position> = (ring..=right)
+                .flat_map(|x| [Position { x, y: ring }, Position { x, y: bottom }])
+                .chain(
+                    (ring + 1..bottom)
+                        .flat_map(|y| [Position { x: ring, y }, Position { x: right, y }]),
+                )
+                .filter(|position| !self.is_obstacle(position))
+                .collect();
+            self.closed_rings += 1;
+
+            if cells.is_empty() {
+                continue;
+            }
+
+            for position in &cells {
+                *self.occupied.entry(*position).or_default() += 1;
+            }
+            self.pickups.retain(|pickup| !cells.contains(&pickup.position));
+            self.closed_cells += cells.len();
+            self.obstacles.extend(cells);
+            self.index_obstacles();
+            return;
+        }
+    }
+
+    /// The points of all players together.
+    fn total_points(&self) -> u32 {
+        self.players.iter().map(|player| player.score).sum()
+    }
+
+    /// Keep count of the rounds in a row that ended without anybody scoring, once a round is over.
+    fn count_drawn_round(&mut self) {
+        if self.total_points() == self.round_start_points {
+            self.drawn_rounds += 1;
+        } else {
+            self.drawn_rounds = 0;
+        }
+    }
+
+    /// Whether the round has gone on for longer than it may. Practice goes on forever.
+    pub fn is_out_of_time(&self) -> bool {
+        !self.practice && self.max_round_ticks.is_some_and(|max| self.round_ticks >= max)
+    }
+
+    /// Decide a round that is out of time in favor of the player with the most room left, which is
+    /// the number of empty cells that it can reach before anyone else. It scores a point for every
+    /// other player that is still in, as if it had outlasted them. Between players with the same
+    /// room, the longest trail decides, and if that is the same too, nobody scores. In a survival
+    /// game, the humans score for making it through the round, as usual.
+    fn score_by_room(&mut self) {
+        if self.survival {
+            self.score();
+            return;
+        }
+
+        // the players that are out have no room left, whatever their trails are
+        let standings: Vec<_> = bot::territories(self)
+            .into_iter()
+            .zip(&self.players)
+            .map(|(room, player)| (!player.eliminated).then_some((room, player.segments.len())))
+            .collect();
+        let Some(best) = standings.iter().max().copied() else {
+            return;
+        };
+
+        let others = standings.iter().flatten().count().saturating_sub(1) as u32;
+        let winners: Vec<_> = (0..standings.len()).filter(|i| standings[*i] == best).collect();
+        if let [winner] = winners[..] {
+            self.players[winner].score += others;
+        }
+    }
+
+    /// Award a point to every player in the round except the one who just crashed, so that by the
+    /// end of the round, every player has scored a point for each player that it outlasted. When
+    /// several players crash at once, the tie rule decides whether the others score for each of
+    /// them. In a survival game, only the
+    /// humans score, for making it through the round.
+    pub fn score(&mut self) {
+        let mut crashed = self.crashed_players();
+
+        if self.survival {
+            for (i, player) in self.players.iter_mut().enumerate() {
+                if !matches!(player.controller, Controller::Bot(_)) && !crashed.contains(&i) {
+                    player.score += 1;
+                }
+            }
+            return;
+        }
+
+        if crashed.len() > 1 && self.tie_rule == TieRule::NoPoints {
+            crashed.clear();
+        }
+
+        for _ in &crashed {
+            for (i, player) in self.players.iter_mut().enumerate() {
+                if !crashed.contains(&i) && !player.eliminated {
+                    player.score += 1;
+                }
+            }
+        }
+    }
+
+    fn reset_players(&mut self) {
+        let num_players = self.players.len();
+
+        // the rings that closed in on the last round open up again
+        self.obstacles.truncate(self.obstacles.len().saturating_sub(self.closed_cells));
+        self.closed_rings = 0;
+        self.closed_cells = 0;
+        self.steps_until_shrink = self.shrink_interval.unwrap_or(0);
+        self.round_ticks = 0;
+        self.round_start_points = self.total_points();
+        self.index_obstacles();
+
+        for (i, player) in self.players.iter_mut().enumerate() {
+            let (position, direction) =
+                starting_state(i, num_players, self.grid_width, self.grid_height);
+            player.segments = VecDeque::from(vec![(position, direction)]);
+            player.pending_directions.clear();
+            player.trimmed_segments = 0;
+            player.boost_turns = 0;
+            player.boost_charge = MAX_BOOST_CHARGE;
+            player.ghost_charges = 0;
+            player.ghost_position = None;
+            player.eliminated = false;
+            player.eliminated_at = 0;
+        }
+
+        // a longer trail reaches back from the head in a straight line, but stops short of the
+        // border, the obstacles and the other trails
+        for i in 0..num_players {
+            for _ in 1..self.start_length {
+                let (tail, direction) = self.players[i].segments[0];
+                let behind = tail.next(&direction.opposite(), self.grid_width, self.grid_height);
+                let is_taken = self.is_on_border(&behind)
+                    || self.is_obstacle(&behind)
+                    || self.players.iter().any(|p| p.segments.iter().any(|(s, _)| *s == behind));
+
+                if is_taken {
+                    break;
+                }
+                self.players[i].segments.push_front((behind, direction));
+            }
+        }
+
+        self.pickups.clear();
+        self.bot_decisions.clear();
+        self.steps_until_pickup = PICKUP_INTERVAL_STEPS;
+        self.count_occupied();
+
+        self.active_player = 0;
+    }
+
+    /// Check whether any player has reached the score that wins the game. A survival game is over
+    /// when a human crashes instead, and a practice game is never over.
+    pub fn is_game_over(&self) -> bool {
+        if self.practice {
+            return false;
+        }
+
+        if self.drawn_rounds >= MAX_DRAWN_ROUNDS {
+            return true;
+        }
+
+        if self.survival {
+            return self
+                .crashed_players()
+                .iter()
+                .any(|i| !matches!(self.players[*i].controller, Controller::Bot(_)));
+        }
+
+        for player in &self.players {
+            if player.score >= self.winning_score() {
+                return true;
+            }
+        }
+
+        false
+    }
+
+    /// The score that wins the game, see [`winning_score`].
+    pub fn winning_score(&self) -> u32 {
+        winning_score(self.max_score, self.players.len())
+    }
+
+    /// The players with the highest score, by index. After the game is over, these are the
+    /// winners; more than one means the game ended in a draw.
+    pub fn leaders(&self) -> Vec<usize> {
+        let best = self.players.iter().map(|p| p.score).max().unwrap_or(0);
+
+        (0..self.players.len())
+            .filter(|i| self.players[*i].score == best)
+            .collect()
+    }
+
+    /// The player with the highest score, by index. When several players reach the score to win
+    /// in the same tick, the one with the most points wins, and if they have the same number of
+    /// points, nobody does. A game that is over after too many drawn rounds has no winner either.
+    /// Before the game is over, this is the player who is ahead.
+    pub fn winner(&self) -> Option<usize> {
+        if self.drawn_rounds >= MAX_DRAWN_ROUNDS {
+            return None;
+        }
+
+        match self.leaders().as_slice() {
+            [winner] => Some(*winner),
+            _ => None,
+        }
+    }
+
+    /// Whether the game is over without a winner, because the last players crashed at the same
+    /// time with the same score, or because nobody scored for `MAX_DRAWN_ROUNDS` rounds in a row.
+    pub fn is_draw(&self) -> bool {
+        self.is_game_over() && self.winner().is_none()
+    }
+
+    /// Add another bot for the next round of a survival game, with the same difficulty as the
+    /// other bots and a color that nobody has yet. Returns whether there was room for the bot.
+    fn add_survival_bot(&mut self) -> bool {
+        let num_bots = self
+            .players
+            .iter()
+            .filter(|p| matches!(p.controller, Controller::Bot(_)))
+            .count();
+        let Some(controller) = self
+            .players
+            .iter()
+            .map(|p| p.controller.clone())
+            .find(|c| matches!(c, Controller::Bot(_)))
+        else {
+            return false;
+        };
+
+        if num_bots >= MAX_SURVIVAL_BOTS {
+            return false;
+        }
+
+        let index = self.players.len();
+        let color = self
+            .palette
+            .swatches()
+            .into_iter()
+            .find(|c| self.players.iter().all(|p| p.color != *c))
+            .unwrap_or(Color::palette(index + 1)[index]);
+        // the position doesn't matter, because the players are reset before the next round
+        let (position, direction) =
+            starting_state(index, index + 1, self.grid_width, self.grid_height);
+        self.players.push(Player::new(color, position, direction, controller));
+        true
+    }
+
+    /// Hand the turn to the next player that is still in the round, so that the players that are
+    /// out never move again. When the active player is the only one left, it keeps the turn, but
+    /// the round is over by then: the crash that would leave a single survivor ends it instead of
+    /// eliminating anyone.
+    pub fn set_next_player(&mut self) {
+        let num_players = self.players.len();
+        let next = (1..=num_players)
+            .map(|offset| (self.active_player + offset) % num_players)
+            .find(|i| !self.players[*i].eliminated);
+
+        if let Some(next) = next {
+            self.active_player = next;
+        }
+    }
+}
+
+/// Remove one obstacle or segment from the count of the specified cell.
+fn vacate(occupied: &mut HashMap<Position, u32>, position: &Position) {
+    if let Some(count) = occupied.get_mut(position) {
+        *count -= 1;
+        if *count == 0 {
+            occupied.remove(position);
+        }
+    }
+}
+
+/// The score that wins a game of the specified number of players. The winner of a round scores a
+/// point for every other player, so with more than two players, it takes `max_score` points for
+/// each of them, to play about as many rounds as with two.
+pub fn winning_score(max_score: u32, num_players: usize) -> u32 {
+    max_score * (num_players.saturating_sub(1).max(1) as u32)
+}
+
+/// Determine the starting position and direction of the player with the specified index. Players
+/// are spread evenly over a ring that is inset from the border, starting in the top left and going
+/// anti-clockwise. Each player faces along the ring, so that nobody starts out driving towards the
+/// border or towards another player. In small arenas, the ring moves closer to the border, but it
+/// always stays inside it.
+fn starting_state(index: usize, total: usize, width: usize, height: usize) -> (Position, Direction) {
+    // the ring is at least two cells across, otherwise its sides would run over each other
+    let inset = STARTING_INSET.min(width.min(height).saturating_sub(3) / 2).max(1);
+    let ring_width = width.saturating_sub(2 * inset + 1);
+    let ring_height = height.saturating_sub(2 * inset + 1);
+    let perimeter = 2 * (ring_width + ring_height);
+    let mut offset = index * perimeter / total.max(1);
+
+    // walk the ring anti-clockwise: down the left side, along the bottom, up the right side and
+    // back along the top
+    let legs = [
+        (ring_height, Direction::South),
+        (ring_width, Direction::East),
+        (ring_height, Direction::North),
+        (ring_width, Direction::West),
+    ];
+    let mut position = Position { x: inset, y: inset };
+
+    for (length, direction) in legs {
+        if offset < length {
+            for _ in 0..offset {
+                position = position.next(&direction, width, height);
+            }
+            return (position, direction);
+        }
+
+        for _ in 0..length {
+            position = position.next(&direction, width, height);
+        }
+        offset -= length;
+    }
+
+    (position, Direction::South)
+}
+
+/// Generate the obstacles of the specified layout, for an arena of the specified width and height.
+/// All layouts are surrounded by the border wall. The obstacles inside are kept away from the
+/// starting positions of the players, and they never touch each other or the border, so that the
+/// wall type of every obstacle can be determined from its neighbors.
+pub fn generate_obstacles(layout: ArenaLayout, width: usize, height: usize) -> Vec<Position> {
+    // the area inside which obstacles may be placed, inclusive, which small arenas don't have
+    let margin = 7;
+    if width < 2 * margin + 1 || height < 2 * margin + 1 {
+        return generate_wall(width, height);
+    }
+    let (left, right) = (margin, width - 1 - margin);
+    let (top, bottom) = (margin, height - 1 - margin);
+    let (center_x, center_y) = ((left + right) / 2, (top + bottom) / 2);
+
+    // obstacles are the outlines of filled rectangles, given as (left, top, right, bottom)
+    let rectangles = match layout {
+        ArenaLayout::Border => vec![],
+        ArenaLayout::Cross => vec![
+            (left, center_y - 1, right, center_y + 1),
+            (center_x - 1, top, center_x + 1, bottom),
+        ],
+        ArenaLayout::Blocks => {
+            let mut blocks = vec![];
+            for y in (top..bottom).step_by(5) {
+                for x in (left..right).step_by(5) {
+                    blocks.push((x, y, x + 1, y + 1));
+                }
+            }
+            blocks
+        }
+        ArenaLayout::Maze => vec![
+            (left, top, center_x - 1, top + 2),
+            (right - 2, top, right, center_y - 1),
+            (center_x + 1, bottom - 2, right, bottom),
+            (left, center_y + 1, left + 2, bottom),
+        ],
+    };
+
+    let is_filled = |x: usize, y: usize| {
+        rectangles
+            .iter()
+            .any(|(l, t, r, b)| (*l..=*r).contains(&x) && (*t..=*b).contains(&y))
+    };
+
+    let mut obstacles = generate_wall(width, height);
+
+    for y in top..=bottom {
+        for x in left..=right {
+            let on_outline = is_filled(x, y)
+                && (y - 1..=y + 1).any(|ny| (x - 1..=x + 1).any(|nx| !is_filled(nx, ny)));
+
+            if on_outline {
+                obstacles.push(Position { x, y });
+            }
+        }
+    }
+
+    obstacles
+}
+
+/// Generate a wall with the specified width and height. The wall starts at the
+/// top middle and goes anti-clockwise around the grid.
+fn generate_wall(width: usize, height: usize) -> Vec<Position> {
+    let mut walls = vec![];
+
+    for i in 1..(width - 1) {
+        walls.push(Position {
+            x: width - 1 - i,
+            y: 0,
+        });
+    }
+
+    for i in 0..height {
+        walls.push(Position { x: 0, y: i });
+    }
+
+    for i in 1..(width - 1) {
+        walls.push(Position {
+            x: i,
+            y: height - 1,
+        });
+    }
+
+    for i in 0..height {
+        walls.push(Position {
+            x: width - 1,
+            y: height - 1 - i,
+        });
+    }
+
+    walls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player_heading(direction: Direction) -> Player {
+        Player::new(Color::red(), Position { x: 5, y: 5 }, direction, Controller::Wasd)
+    }
+
+    #[test]
+    fn set_direction_rejects_reversing_on_every_heading() {
+        for heading in Direction::ALL {
+            let mut player = player_heading(heading);
+            player.set_direction(heading.opposite());
+            assert!(player.pending_directions.is_empty(), "reversing {:?}", heading);
+        }
+    }
+
+    #[test]
+    fn set_direction_accepts_turns_on_every_heading() {
+        for heading in Direction::ALL {
+            for turn in [heading.turn_left(), heading.turn_right()] {
+                let mut player = player_heading(heading);
+                player.set_direction(turn);
+                let message = format!("turning {:?} from {:?}", turn, heading);
+                assert_eq!(player.pending_directions, [turn], "{}", message);
+            }
+        }
+    }
+
+    /// The players that crashed, found by scanning every obstacle and every segment of every
+    /// player, without the count of occupied cells.
+    fn scanned_crashes(game_state: &GameState) -> Vec<usize> {
+        let players = &game_state.players;
+        let heads: Vec<Position> = players.iter().map(|p| p.segments.back().unwrap().0).collect();
+
+        (0..players.len())
+            .filter(|&i| !players[i].eliminated)
+            .filter(|&i| {
+                let head = heads[i];
+                let is_ghosting = players[i].ghost_position == Some(head);
+                let hits_head = (0..players.len()).any(|j| j != i && heads[j] == head);
+
+                let obstacles = game_state.obstacles.iter().filter(|p| **p == head).count();
+                let segments = players
+                    .iter()
+                    .flat_map(|p| p.segments.iter())
+                    .filter(|(p, _)| *p == head)
+                    .count();
+                let other_heads = heads.iter().filter(|p| **p == head).count();
+                let hits_wall = obstacles + segments > other_heads;
+
+                hits_head || (!is_ghosting && hits_wall)
+            })
+            .collect()
+    }
+
+    fn random_position(rng: &mut Rng, width: usize, height: usize) -> Position {
+        Position { x: rng.below(width), y: rng.below(height) }
+    }
+
+    #[test]
+    fn occupied_count_finds_the_same_crashes_as_a_scan_on_random_boards() {
+        let mut rng = Rng::seeded(293);
+        let colors = [Color::red(), Color::blue(), Color::green(), Color::yellow()];
+
+        for _ in 0..500 {
+            let num_players = 2 + rng.below(3);
+            let mut game_state =
+                GameState::new(0, num_players, BotDifficulty::Easy, 3, 12, 10, &colors);
+
+            // random trails, which may cross each other, themselves and the obstacles
+            let (width, height) = (game_state.grid_width, game_state.grid_height);
+            for player in &mut game_state.players {
+                let length = 1 + rng.below(20);
+                player.segments = (0..length)
+                    .map(|_| (random_position(&mut rng, width, height), Direction::North))
+                    .collect();
+            }
+            for player in &mut game_state.players {
+                player.eliminated = rng.below(4) == 0;
+                if rng.below(4) == 0 {
+                    player.ghost_position = player.segments.back().map(|(p, _)| *p);
+                }
+            }
+            game_state.count_occupied();
+
+            assert_eq!(game_state.crashed_players(), scanned_crashes(&game_state));
+        }
+    }
+
+    #[test]
+    fn occupied_count_is_kept_up_to_date_while_playing() {
+        let colors = [Color::red(), Color::blue(), Color::green(), Color::yellow()];
+
+        for seed in 0..8 {
+            let mut game_state = GameState::new(0, 4, BotDifficulty::Easy, 3, 16, 12, &colors);
+            game_state.rng = Rng::seeded(seed);
+            game_state.pickups_enabled = true;
+            game_state.max_trail_length = (seed % 2 == 0).then_some(10);
+            if seed % 4 < 2 {
+                game_state.set_arena(ArenaMode::Wrap, ArenaLayout::Blocks);
+            }
+
+            for _ in 0..3000 {
+                game_state.tick();
+
+                let mut counted = game_state.clone();
+                counted.count_occupied();
+                assert_eq!(game_state.occupied, counted.occupied);
+                if game_state.phase == Phase::Step {
+                    assert_eq!(game_state.crashed_players(), scanned_crashes(&game_state));
+                }
+            }
+        }
+    }
+
+    /// Whether the obstacle cells are the same as a scan of the list of obstacles finds.
+    fn obstacle_cells_match_a_scan(game_state: &GameState) -> bool {
+        let cells: HashSet<Position> = game_state.obstacles.iter().copied().collect();
+        *game_state.obstacle_cells() == cells
+    }
+
+    #[test]
+    fn obstacle_collisions_are_the_same_as_with_a_scan() {
+        let colors = [Color::red(), Color::blue()];
+        let layouts =
+            [ArenaLayout::Border, ArenaLayout::Cross, ArenaLayout::Blocks, ArenaLayout::Maze];
+
+        for arena_mode in [ArenaMode::Walled, ArenaMode::Wrap] {
+            for layout in layouts {
+                let mut game_state =
+                    GameState::new(2, 2, BotDifficulty::Easy, 3, 20, 16, &colors);
+                game_state.set_arena(arena_mode, layout);
+                assert!(obstacle_cells_match_a_scan(&game_state));
+
+                for y in 0..game_state.grid_height {
+                    for x in 0..game_state.grid_width {
+                        let position = Position { x, y };
+                        let is_obstacle = game_state.obstacles.contains(&position);
+                        assert_eq!(game_state.is_obstacle(&position), is_obstacle);
+
+                        // a head on the cell crashes exactly when a scan finds an obstacle there
+                        let mut moved = game_state.clone();
+                        moved.players[0].segments = VecDeque::from([(position, Direction::East)]);
+                        moved.count_occupied();
+                        let expected = scanned_crashes(&moved);
+                        assert_eq!(moved.crashed_players(), expected);
+
+                        let trail = &moved.players[1].segments;
+                        if !trail.iter().any(|(p, _)| *p == position) {
+                            assert_eq!(expected.contains(&0), is_obstacle, "{:?}", position);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn obstacle_cells_follow_the_closing_rings() {
+        let colors = [Color::red(), Color::blue()];
+        let mut game_state = GameState::new(0, 2, BotDifficulty::Medium, 3, 20, 16, &colors);
+        game_state.set_seed(332);
+        game_state.set_shrink_interval(Some(4));
+
+        let mut closed_rings = false;
+        for _ in 0..3000 {
+            game_state.tick();
+            closed_rings |= game_state.closed_rings > 0;
+            assert!(obstacle_cells_match_a_scan(&game_state));
+        }
+        assert!(closed_rings);
+    }
+
+    #[test]
+    fn distance_goes_across_the_edges_only_in_a_wrapping_arena() {
+        let colors = [Color::red(), Color::blue()];
+        let mut game_state = GameState::new(2, 2, BotDifficulty::Easy, 3, 20, 16, &colors);
+        let (left, right) = (Position { x: 1, y: 3 }, Position { x: 18, y: 3 });
+
+        game_state.set_arena(ArenaMode::Walled, ArenaLayout::Border);
+        assert_eq!(game_state.distance(&left, &right), 17);
+
+        game_state.set_arena(ArenaMode::Wrap, ArenaLayout::Border);
+        assert_eq!(game_state.distance(&left, &right), 3);
+    }
+
+    #[test]
+    fn winning_score_takes_max_score_points_for_every_other_player() {
+        assert_eq!(winning_score(3, 1), 3);
+        assert_eq!(winning_score(3, 2), 3);
+        assert_eq!(winning_score(3, 3), 6);
+        assert_eq!(winning_score(3, 4), 9);
+
+        let colors = [Color::red(), Color::blue(), Color::green()];
+        let game_state = GameState::new(1, 3, BotDifficulty::Easy, 2, 20, 16, &colors);
+        assert_eq!(game_state.winning_score(), winning_score(2, 3));
+    }
+
+    /// Check that the players of the game start inside the arena, each on a different empty cell,
+    /// facing an empty cell.
+    fn assert_valid_starts(game_state: &GameState) {
+        let (width, height) = (game_state.grid_width, game_state.grid_height);
+        let heads: Vec<(Position, Direction)> =
+            game_state.players.iter().map(|p| *p.segments.back().unwrap()).collect();
+        let description = format!("{} players in {} by {}", heads.len(), width, height);
+
+        for (i, (position, direction)) in heads.iter().enumerate() {
+            assert!(position.x < width && position.y < height, "{}", description);
+            assert!(!game_state.is_obstacle(position), "{}", description);
+            assert!(heads.iter().skip(i + 1).all(|(p, _)| p != position), "{}", description);
+
+            let ahead = game_state.next_position(position, direction);
+            let ahead = ahead.unwrap_or_else(|| panic!("{} faces the edge", description));
+            assert!(!game_state.is_occupied(&ahead), "{} faces a wall", description);
+            assert!(heads.iter().all(|(p, _)| *p != ahead), "{} faces a player", description);
+        }
+        assert!(game_state.crashed_players().is_empty(), "{}", description);
+    }
+
+    #[test]
+    fn small_arenas_have_valid_starts_for_every_number_of_players() {
+        let colors = [Color::red(), Color::blue(), Color::green(), Color::yellow()];
+        let presets = [ArenaShape::Classic, ArenaShape::Square, ArenaShape::Wide]
+            .map(|shape| ArenaSize::Small.dimensions(shape));
+        let small = (5..=12).flat_map(|width| (5..=12).map(move |height| (width, height)));
+
+        let layouts =
+            [ArenaLayout::Border, ArenaLayout::Cross, ArenaLayout::Blocks, ArenaLayout::Maze];
+
+        for (width, height) in small.chain(presets) {
+            for num_players in 1..=4 {
+                let mut game_state =
+                    GameState::new(0, num_players, BotDifficulty::Easy, 3, width, height, &colors);
+                assert_valid_starts(&game_state);
+
+                for arena_mode in [ArenaMode::Walled, ArenaMode::Wrap] {
+                    for layout in layouts {
+                        game_state.set_arena(arena_mode, layout);
+                        assert_valid_starts(&game_state);
+
+                        // the next round starts from the same positions
+                        game_state.reset_players();
+                        assert_valid_starts(&game_state);
+                    }
+                }
+            }
+        }
+    }
+
+    /// A game of three humans on a roomy arena, in which the middle player is out.
+    fn game_without_the_middle_player() -> GameState {
+        let colors = [Color::red(), Color::blue(), Color::green()];
+        let mut game_state = GameState::new(3, 3, BotDifficulty::Easy, 3, 32, 28, &colors);
+        game_state.set_phase(Phase::Step);
+        game_state.players[1].eliminated = true;
+        game_state
+    }
+
+    #[test]
+    fn set_next_player_skips_the_eliminated_middle_player() {
+        let mut game_state = game_without_the_middle_player();
+
+        let mut turns = vec![game_state.active_player];
+        for _ in 0..4 {
+            game_state.set_next_player();
+            turns.push(game_state.active_player);
+        }
+        assert_eq!(turns, [0, 2, 0, 2, 0]);
+    }
+
+    #[test]
+    fn set_next_player_keeps_the_turn_for_the_last_player_left() {
+        let mut game_state = game_without_the_middle_player();
+        game_state.players[2].eliminated = true;
+
+        game_state.set_next_player();
+        assert_eq!(game_state.active_player, 0);
+    }
+
+    #[test]
+    fn eliminated_middle_player_doesnt_move_while_the_others_take_turns() {
+        let mut game_state = game_without_the_middle_player();
+        let trail = game_state.players[1].segments.clone();
+
+        let mut turns = vec![];
+        for _ in 0..6 {
+            turns.push(game_state.active_player);
+            game_state.tick();
+        }
+
+        assert_eq!(game_state.phase, Phase::Step);
+        assert_eq!(turns, [0, 2, 0, 2, 0, 2]);
+        assert_eq!(game_state.players[0].segments.len(), 4);
+        assert_eq!(game_state.players[1].segments, trail);
+        assert_eq!(game_state.players[2].segments.len(), 4);
+    }
+
+    /// A game of humans in which the first player is about to drive onto the head of the second
+    /// player, with the tie rule that both of them lose.
+    fn head_on_game(num_players: usize) -> GameState {
+        let colors = [Color::red(), Color::blue(), Color::green()];
+        let mut game_state =
+            GameState::new(num_players, num_players, BotDifficulty::Easy, 3, 32, 28, &colors);
+        game_state.set_phase(Phase::Step);
+        game_state.tie_rule = TieRule::BothLose;
+        let heads = [(15, Direction::East), (16, Direction::West)];
+        for (player, (x, direction)) in game_state.players.iter_mut().zip(heads) {
+            player.segments = VecDeque::from([(Position { x, y: 14 }, direction)]);
+        }
+        game_state.count_occupied();
+        game_state.active_player = 0;
+        game_state
+    }
+
+    fn scores(game_state: &GameState) -> Vec<u32> {
+        game_state.players.iter().map(|player| player.score).collect()
+    }
+
+    #[test]
+    fn both_lose_gives_no_points_for_a_head_on_crash_of_two_players() {
+        let mut game_state = head_on_game(2);
+        game_state.tick();
+
+        assert_eq!(game_state.phase, Phase::Collision);
+        assert_eq!(game_state.crashed_players(), [0, 1]);
+        assert_eq!(scores(&game_state), [0, 0]);
+    }
+
+    #[test]
+    fn both_lose_gives_points_only_to_the_survivor_of_a_head_on_crash() {
+        let mut game_state = head_on_game(3);
+        game_state.tick();
+
+        assert_eq!(game_state.phase, Phase::Collision);
+        assert_eq!(game_state.crashed_players(), [0, 1]);
+        assert_eq!(scores(&game_state), [0, 0, 2]);
+    }
+}
@@ -0,0 +1,633 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use crate::common::{Color, Direction, Position, TrailPattern};
+use crate::game::{GameState, Phase, PickupEffect, Player};
+use std::{
+    collections::{HashSet, VecDeque},
+    fmt::{self, Debug, Formatter},
+};
+
+#[derive(Debug)]
+pub enum WallError {
+    SelfCollision,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WallType {
+    Horizontal,
+    Vertical,
+    CornerTopLeft,
+    CornerTopRight,
+    CornerBottomLeft,
+    CornerBottomRight,
+    /// The end of a wall, connected only in the specified direction.
+    End(Direction),
+    /// A T-junction, connected in every direction except the specified one.
+    Tee(Direction),
+    /// A junction connected in all four directions.
+    Cross,
+    /// A wall without any neighbors.
+    Single,
+}
+
+impl WallType {
+    /// Calculate wall type from current and previous directions. The first segment is the end
+    /// of the trail, which is only connected to the segment after it. Only the directions count,
+    /// not the positions, so a trail that wraps around the edge of the arena stays straight on
+    /// both sides of the edge, even though its positions jump to the other side.
+    pub fn calculate_from_directions(
+        i: usize,
+        segments: &VecDeque<(Position, Direction)>,
+    ) -> Result<WallType, WallError> {
+        if i == 0 {
+            return Ok(WallType::End(segments[0].1));
+        }
+
+        let from = segments[i - 1].1;
+        let to = segments[i].1;
+
+        match (from, to) {
+            (Direction::North, Direction::North) => Ok(WallType::Vertical),
+            (Direction::South, Direction::South) => Ok(WallType::Vertical),
+            (Direction::West, Direction::West) => Ok(WallType::Horizontal),
+            (Direction::East, Direction::East) => Ok(WallType::Horizontal),
+            (Direction::North, Direction::West) => Ok(WallType::CornerTopRight),
+            (Direction::North, Direction::East) => Ok(WallType::CornerTopLeft),
+            (Direction::South, Direction::West) => Ok(WallType::CornerBottomRight),
+            (Direction::South, Direction::East) => Ok(WallType::CornerBottomLeft),
+            (Direction::East, Direction::North) => Ok(WallType::CornerBottomRight),
+            (Direction::East, Direction::South) => Ok(WallType::CornerTopRight),
+            (Direction::West, Direction::North) => Ok(WallType::CornerBottomLeft),
+            (Direction::West, Direction::South) => Ok(WallType::CornerTopLeft),
+            (Direction::North, Direction::South)
+            | (Direction::South, Direction::North)
+            | (Direction::West, Direction::East)
+            | (Direction::East, Direction::West) => Err(WallError::SelfCollision),
+        }
+    }
+
+    /// Calculate wall type from obstacles: the wall type is determined by which of the four
+    /// neighboring cells are also obstacles. For example, if there are obstacles south and west
+    /// of the current one, the wall type is CornerTopRight. This works for any shape, including
+    /// separate groups of obstacles and single blocks.
+    pub fn calculate_from_positions(position: Position, obstacles: &HashSet<Position>) -> WallType {
+        let is_obstacle = |x: Option<usize>, y: Option<usize>| match (x, y) {
+            (Some(x), Some(y)) => obstacles.contains(&Position { x, y }),
+            _ => false,
+        };
+
+        let (x, y) = (position.x, position.y);
+        let north = is_obstacle(Some(x), y.checked_sub(1));
+        let south = is_obstacle(Some(x), Some(y + 1));
+        let west = is_obstacle(x.checked_sub(1), Some(y));
+        let east = is_obstacle(Some(x + 1), Some(y));
+
+        match (north, south, west, east) {
+            (true, true, false, false) => WallType::Vertical,
+            (false, false, true, true) => WallType::Horizontal,
+            (false, true, false, true) => WallType::CornerTopLeft,
+            (false, true, true, false) => WallType::CornerTopRight,
+            (true, false, false, true) => WallType::CornerBottomLeft,
+            (true, false, true, false) => WallType::CornerBottomRight,
+            (true, false, false, false) => WallType::End(Direction::North),
+            (false, true, false, false) => WallType::End(Direction::South),
+            (false, false, true, false) => WallType::End(Direction::West),
+            (false, false, false, true) => WallType::End(Direction::East),
+            (false, true, true, true) => WallType::Tee(Direction::North),
+            (true, false, true, true) => WallType::Tee(Direction::South),
+            (true, true, false, true) => WallType::Tee(Direction::West),
+            (true, true, true, false) => WallType::Tee(Direction::East),
+            (true, true, true, true) => WallType::Cross,
+            (false, false, false, false) => WallType::Single,
+        }
+    }
+}
+
+/// How many segments behind the head of a player still glow. Older segments are drawn dark.
+pub const GLOW_SEGMENTS: usize = 8;
+
+#[derive(Copy, Clone, Debug)]
+pub enum Cell {
+    /// A piece of wall, with its shape, its color, how brightly it glows, from 0.0 (an old
+    /// trail) to 1.0 (just behind the head, or an obstacle), and the pattern of its line.
+    Wall(WallType, Color, f64, TrailPattern),
+    /// The head of a player, with its direction, its color and whether it can pass through a wall.
+    Player(Direction, Color, bool),
+    Collision,
+    /// An explosion at a crash site, with the current animation frame and the color of the
+    /// player that crashed.
+    Explosion(u32, Color),
+    Letter(char, Color),
+    Pickup(PickupEffect),
+    Empty,
+}
+
+impl Cell {
+    pub fn head_from_player(player: &Player) -> Self {
+        let (_, direction) = player.segments.back().unwrap();
+        Cell::Player(*direction, player.color, player.ghost_charges > 0)
+    }
+}
+
+/// The cells of the arena, stored row by row.
+#[derive(Clone)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    data: Vec<Cell>,
+}
+
+impl Grid {
+    pub fn new(width: usize, height: usize, game_state: &GameState) -> Self {
+        let mut grid = Grid {
+            width,
+            height,
+            data: Grid::init_data(width, height),
+        };
+        grid.place_objects(game_state);
+        grid
+    }
+
+    pub fn reset(&mut self, game_state: &GameState) {
+        self.width = game_state.grid_width;
+        self.height = game_state.grid_height;
+        self.data = Grid::init_data(self.width, self.height);
+        self.place_objects(game_state);
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The cell in column `x` of row `y`, or `None` if that is outside the grid.
+    pub fn cell_at(&self, x: usize, y: usize) -> Option<&Cell> {
+        let index = self.index(x, y)?;
+        self.data.get(index)
+    }
+
+    /// The cell in column `x` of row `y` to change, or `None` if that is outside the grid.
+    pub fn cell_at_mut(&mut self, x: usize, y: usize) -> Option<&mut Cell> {
+        let index = self.index(x, y)?;
+        self.data.get_mut(index)
+    }
+
+    /// Where the cell in column `x` of row `y` is in `data`. A column past the right edge would
+    /// otherwise end up in the next row, so it is outside the grid just like a row past the bottom.
+    fn index(&self, x: usize, y: usize) -> Option<usize> {
+        (x < self.width && y < self.height).then(|| y * self.width + x)
+    }
+
+    /// The cell in column `x` of row `y`. Outside the grid, every cell is empty.
+    pub fn get(&self, x: usize, y: usize) -> Cell {
+        self.cell_at(x, y).copied().unwrap_or(Cell::Empty)
+    }
+
+    /// Replace the cell in column `x` of row `y`. Outside the grid, nothing is placed.
+    pub fn set(&mut self, x: usize, y: usize, cell: Cell) {
+        if let Some(old) = self.cell_at_mut(x, y) {
+            *old = cell;
+        }
+    }
+
+    fn place_objects(&mut self, game_state: &GameState) {
+        self.place_obstacles(game_state);
+        self.place_pickups(game_state);
+        self.place_players(game_state);
+        self.place_collision(game_state);
+        self.place_countdown(game_state);
+    }
+
+    fn init_data(width: usize, height: usize) -> Vec<Cell> {
+        vec![Cell::Empty; width * height]
+    }
+
+    fn place_obstacles(&mut self, game_state: &GameState) {
+        let obstacles = game_state.obstacle_cells();
+
+        for obstacle in &game_state.obstacles {
+            self.set(
+                obstacle.x,
+                obstacle.y,
+                Cell::Wall(
+                    WallType::calculate_from_positions(*obstacle, obstacles),
+                    game_state.palette.obstacle(),
+                    1.0,
+                    TrailPattern::Solid,
+                ),
+            );
+        }
+    }
+
+    fn place_pickups(&mut self, game_state: &GameState) {
+        for pickup in &game_state.pickups {
+            self.set(pickup.position.x, pickup.position.y, Cell::Pickup(pickup.effect));
+        }
+    }
+
+    fn place_players(&mut self, game_state: &GameState) {
+        for player in game_state.players.iter() {
+            for i in 0..player.segments.len() {
+                self.place_segment(player, i);
+            }
+        }
+    }
+
+    /// Place the segment of the player with the specified index: the head, or a piece of trail.
+    fn place_segment(&mut self, player: &Player, i: usize) {
+        let (position, _) = player.segments[i];
+
+        if i == player.segments.len() - 1 {
+            self.set(position.x, position.y, Cell::head_from_player(player));
+        } else {
+            match WallType::calculate_from_directions(i, &player.segments) {
+                Ok(wall_type) => {
+                    let age = player.segments.len() - 1 - i;
+                    let glow = 1.0 - (age.min(GLOW_SEGMENTS) as f64 / GLOW_SEGMENTS as f64);
+                    let cell = Cell::Wall(wall_type, player.color, glow, player.pattern);
+                    self.set(position.x, position.y, cell);
+                }
+                Err(_) => {
+                    self.set(position.x, position.y, Cell::Collision);
+                }
+            }
+        }
+    }
+
+    /// Update the grid from the `old` game state to the `new` one, by only changing the cells of
+    /// trails that have grown or shrunk. This is much cheaper than a reset while the players are
+    /// moving. Anything else, such as a crash or a new round, falls back to a full reset.
+    pub fn apply_delta(&mut self, old: &GameState, new: &GameState) {
+        let is_moving = |s: &GameState| matches!(s.phase, Phase::Step | Phase::Paused);
+
+        let same_arena = old.grid_width == new.grid_width
+            && old.grid_height == new.grid_height
+            && old.obstacles == new.obstacles
+            && old.pickups == new.pickups
+            && old.players.len() == new.players.len()
+            && old.players.iter().zip(&new.players).all(|(o, n)| o.eliminated == n.eliminated);
+
+        if !same_arena || !is_moving(old) || !is_moving(new) || new.has_collision() {
+            self.reset(new);
+            return;
+        }
+
+        // every trail must be the old trail with segments removed from its tail and added at its
+        // head, otherwise the difference can't be applied
+        let mut changes = Vec::with_capacity(new.players.len());
+        for (old_player, new_player) in old.players.iter().zip(&new.players) {
+            let trimmed = new_player.trimmed_segments.checked_sub(old_player.trimmed_segments);
+
+            match trimmed {
+                Some(trimmed)
+                    if trimmed <= old_player.segments.len()
+                        && old_player.color == new_player.color
+                        && new_player.segments.len() >= old_player.segments.len() - trimmed =>
+                {
+                    changes.push((trimmed, old_player.segments.len() - trimmed));
+                }
+                _ => {
+                    self.reset(new);
+                    return;
+                }
+            }
+        }
+
+        // clear the removed segments first, since another player may have moved onto them
+        let mut cleared = HashSet::new();
+        for (old_player, (trimmed, _)) in old.players.iter().zip(&changes) {
+            for (position, _) in old_player.segments.iter().take(*trimmed) {
+                self.set(position.x, position.y, Cell::Empty);
+                cleared.insert(*position);
+            }
+        }
+
+        // a trail that crossed itself, or a ghost that drove through another trail, can still be
+        // on a cleared cell
+        cleared.retain(|position| new.is_occupied(position));
+        if !cleared.is_empty() {
+            for new_player in &new.players {
+                for (i, (position, _)) in new_player.segments.iter().enumerate() {
+                    if cleared.contains(position) {
+                        self.place_segment(new_player, i);
+                    }
+                }
+            }
+        }
+
+        for (new_player, (trimmed, kept)) in new.players.iter().zip(changes) {
+            // the trail of a player that is out doesn't change anymore
+            if new_player.eliminated {
+                continue;
+            }
+
+            // the tail changes when segments were removed, the old head becomes trail and the
+            // segments that were glowing have grown older
+            if trimmed > 0 {
+                self.place_segment(new_player, 0);
+            }
+            for i in kept.saturating_sub(GLOW_SEGMENTS)..new_player.segments.len() {
+                self.place_segment(new_player, i);
+            }
+        }
+    }
+
+    fn place_collision(&mut self, game_state: &GameState) {
+        let crashed = game_state.crashed_players();
+
+        // the players that are out of the round stay where they crashed
+        for player in game_state.players.iter().filter(|p| p.eliminated) {
+            let (position, _) = player.segments.back().unwrap();
+            self.set(position.x, position.y, Cell::Collision);
+        }
+
+        for i in &crashed {
+            let player = &game_state.players[*i];
+            let (position, _) = player.segments.back().unwrap();
+
+            let cell = if game_state.phase == Phase::Collision {
+                Cell::Explosion(game_state.elapsed, player.color)
+            } else {
+                Cell::Collision
+            };
+            self.set(position.x, position.y, cell);
+        }
+
+        // a round can also be over without a crash, when it runs out of time
+        if game_state.phase == Phase::Score {
+            self.place_scores(game_state);
+        }
+    }
+
+    /// Show the countdown before a round in the middle of the grid.
+    fn place_countdown(&mut self, game_state: &GameState) {
+        if game_state.phase != Phase::Countdown {
+            return;
+        }
+
+        let text = match game_state.countdown() {
+            0 => "GO".to_string(),
+            n => n.to_string(),
+        };
+        let x = self.width.saturating_sub(text.len()) / 2;
+        let y = self.height / 2;
+
+        self.place_text(&text, Position { x, y }, Default::default());
+    }
+
+    /// Show the scores of all players in the middle of the grid, like "P1 2 - 1 P2". With more
+    /// than two players, the scores are separated by dashes in the order in which the players
+    /// placed in the round, like "P3 4 - P1 2 - P2 0".
+    fn place_scores(&mut self, game_state: &GameState) {
+        let num_players = game_state.players.len();
+        let order = if num_players > 2 {
+            game_state.placements()
+        } else {
+            (0..num_players).collect()
+        };
+        let parts: Vec<(String, Color)> = order
+            .into_iter()
+            .map(|i| {
+                let player = &game_state.players[i];
+                let text = if num_players == 2 && i == 1 {
+                    format!("{} P{}", player.score, i + 1)
+                } else {
+                    format!("P{} {}", i + 1, player.score)
+                };
+                (text, player.color)
+            })
+            .collect();
+
+        let separator = " - ";
+        let length = parts.iter().map(|(text, _)| text.chars().count()).sum::<usize>()
+            + separator.len() * parts.len().saturating_sub(1);
+        let mut x = self.width.saturating_sub(length) / 2;
+        let y = self.height / 2;
+
+        for (i, (text, color)) in parts.iter().enumerate() {
+            if i > 0 {
+                x = self.place_text(separator, Position { x, y }, Default::default());
+            }
+            x = self.place_text(text, Position { x, y }, *color);
+        }
+    }
+
+    /// Write text onto the grid, one letter per cell, starting at the specified position and going
+    /// to the right. Spaces leave the cells underneath visible. Text that doesn't fit within the
+    /// width of the grid is cut off. Returns the position after the last letter.
+    pub fn place_text(&mut self, text: &str, at: Position, color: Color) -> usize {
+        if at.y >= self.height {
+            return at.x;
+        }
+
+        let mut x = at.x;
+        for letter in text.chars() {
+            if x >= self.width {
+                break;
+            }
+
+            if letter != ' ' {
+                self.set(x, at.y, Cell::Letter(letter, color));
+            }
+            x += 1;
+        }
+
+        x
+    }
+}
+
+impl Debug for Grid {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{{")?;
+        writeln!(f)?;
+        for row in self.data.chunks(self.width.max(1)) {
+            write!(f, "  ")?;
+            for cell in row {
+                match cell {
+                    Cell::Wall(..) => write!(f, "W")?,
+                    Cell::Player(..) => write!(f, "P")?,
+                    Cell::Empty => write!(f, " ")?,
+                    Cell::Collision | Cell::Explosion(..) => write!(f, "X")?,
+                    Cell::Letter(c, _) => write!(f, "{}", c)?,
+                    Cell::Pickup(..) => write!(f, "*")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        write!(f, "}}")?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::BotDifficulty;
+    use crate::game::{ArenaLayout, ArenaMode};
+    use crate::rng::Rng;
+
+    /// A game of two players on a small arena, which has started moving.
+    fn moving_game(num_humans: usize) -> GameState {
+        let colors = [Color::red(), Color::blue()];
+        let mut game_state =
+            GameState::new(num_humans, 2, BotDifficulty::Medium, 3, 16, 12, &colors);
+        game_state.phase = Phase::Step;
+        game_state
+    }
+
+    /// Change the trail of `player` in `game_state`, with `trimmed` segments taken from its tail.
+    fn with_trail(
+        game_state: &GameState,
+        player: usize,
+        trimmed: usize,
+        trail: &[(usize, usize, Direction)],
+    ) -> GameState {
+        let mut game_state = game_state.clone();
+        game_state.players[player].segments =
+            trail.iter().map(|&(x, y, direction)| (Position { x, y }, direction)).collect();
+        game_state.players[player].trimmed_segments += trimmed;
+        game_state.count_occupied();
+        game_state
+    }
+
+    fn assert_delta_matches_reset(old: &GameState, new: &GameState) {
+        let mut grid = Grid::new(old.grid_width, old.grid_height, old);
+        grid.apply_delta(old, new);
+        let expected = Grid::new(new.grid_width, new.grid_height, new);
+        assert_eq!(format!("{:?}", grid), format!("{:?}", expected));
+    }
+
+    #[test]
+    fn delta_clears_the_tail_of_a_trail_that_crosses_itself() {
+        use Direction::*;
+
+        // a ghost drove a loop through its own trail, so (5, 5) is in the trail twice, and four
+        // steps later, the loop is gone from its tail
+        let game_state = moving_game(2);
+        let old = with_trail(
+            &game_state,
+            0,
+            0,
+            &[
+                (5, 5, North),
+                (5, 4, East),
+                (6, 4, South),
+                (6, 5, West),
+                (5, 5, South),
+                (5, 6, South),
+            ],
+        );
+        let new = with_trail(
+            &old,
+            0,
+            4,
+            &[
+                (5, 5, South),
+                (5, 6, South),
+                (5, 7, South),
+                (5, 8, South),
+                (5, 9, South),
+                (5, 10, South),
+            ],
+        );
+
+        assert_delta_matches_reset(&old, &new);
+        let mut grid = Grid::new(old.grid_width, old.grid_height, &old);
+        grid.apply_delta(&old, &new);
+        for (x, y) in [(5, 4), (6, 4), (6, 5)] {
+            assert!(matches!(grid.get(x, y), Cell::Empty));
+        }
+        assert!(matches!(grid.get(5, 5), Cell::Wall(..)));
+    }
+
+    #[test]
+    fn delta_matches_reset_in_a_wrapping_game_with_short_trails() {
+        let mut game_state = moving_game(0);
+        game_state.set_arena(ArenaMode::Wrap, ArenaLayout::Border);
+        game_state.max_trail_length = Some(5);
+        game_state.rng = Rng::seeded(7);
+
+        for _ in 0..2000 {
+            let old = game_state.clone();
+            game_state.tick();
+            assert_delta_matches_reset(&old, &game_state);
+        }
+    }
+
+    fn obstacles(cells: &[(usize, usize)]) -> HashSet<Position> {
+        cells.iter().map(|&(x, y)| Position { x, y }).collect()
+    }
+
+    fn wall_type(obstacles: &HashSet<Position>, x: usize, y: usize) -> WallType {
+        WallType::calculate_from_positions(Position { x, y }, obstacles)
+    }
+
+    #[test]
+    fn single_block_has_no_neighbors() {
+        let obstacles = obstacles(&[(0, 0), (4, 4)]);
+        assert_eq!(wall_type(&obstacles, 0, 0), WallType::Single);
+        assert_eq!(wall_type(&obstacles, 4, 4), WallType::Single);
+    }
+
+    #[test]
+    fn l_shape_has_ends_a_corner_and_straight_pieces() {
+        // #
+        // #
+        // ###
+        let obstacles = obstacles(&[(2, 2), (2, 3), (2, 4), (3, 4), (4, 4)]);
+        assert_eq!(wall_type(&obstacles, 2, 2), WallType::End(Direction::South));
+        assert_eq!(wall_type(&obstacles, 2, 3), WallType::Vertical);
+        assert_eq!(wall_type(&obstacles, 2, 4), WallType::CornerBottomLeft);
+        assert_eq!(wall_type(&obstacles, 3, 4), WallType::Horizontal);
+        assert_eq!(wall_type(&obstacles, 4, 4), WallType::End(Direction::West));
+    }
+
+    #[test]
+    fn plus_shape_has_a_cross_and_four_ends() {
+        //  #
+        // ###
+        //  #
+        let obstacles = obstacles(&[(3, 2), (2, 3), (3, 3), (4, 3), (3, 4)]);
+        assert_eq!(wall_type(&obstacles, 3, 3), WallType::Cross);
+        assert_eq!(wall_type(&obstacles, 3, 2), WallType::End(Direction::South));
+        assert_eq!(wall_type(&obstacles, 3, 4), WallType::End(Direction::North));
+        assert_eq!(wall_type(&obstacles, 2, 3), WallType::End(Direction::East));
+        assert_eq!(wall_type(&obstacles, 4, 3), WallType::End(Direction::West));
+    }
+
+    #[test]
+    fn tee_is_open_on_the_missing_side() {
+        // ###
+        //  #
+        let obstacles = obstacles(&[(1, 1), (2, 1), (3, 1), (2, 2)]);
+        assert_eq!(wall_type(&obstacles, 2, 1), WallType::Tee(Direction::North));
+    }
+
+    #[test]
+    fn blocks_at_the_edge_of_the_arena_are_not_connected_past_it() {
+        let obstacles = obstacles(&[(0, 0), (1, 0), (0, 1)]);
+        assert_eq!(wall_type(&obstacles, 0, 0), WallType::CornerTopLeft);
+        assert_eq!(wall_type(&obstacles, 1, 0), WallType::End(Direction::West));
+        assert_eq!(wall_type(&obstacles, 0, 1), WallType::End(Direction::North));
+    }
+}
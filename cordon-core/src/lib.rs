@@ -0,0 +1,31 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! The rules of Cordon, without anything that is specific to the browser: the game itself, the
+//! bots, the grid of cells that a renderer draws, and keeping the games of two online clients in
+//! step. Enable the `js` feature to use it in the browser.
+
+pub mod benchmark;
+pub mod bot;
+pub mod clock;
+pub mod common;
+pub mod game;
+pub mod layout;
+pub mod lockstep;
+pub mod rng;
@@ -0,0 +1,486 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::VecDeque;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    common::Direction,
+    game::{GameState, Phase},
+};
+
+/// The number of ticks between entering an input and playing it. Both clients play a tick only
+/// when they have the input of both players for it, so this hides the time that the input takes
+/// to reach the other client.
+pub const INPUT_DELAY_TICKS: u64 = 2;
+
+/// The side of an online game that a client is on.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Role {
+    /// The client that sets up the game and plays the first player.
+    Host,
+    /// The client that joins the game of the host and plays the second player.
+    Guest,
+}
+
+impl Role {
+    /// The index of the player that this client steers.
+    pub fn local_player(&self) -> usize {
+        match self {
+            Role::Host => 0,
+            Role::Guest => 1,
+        }
+    }
+
+    /// The index of the player that the other client steers.
+    pub fn remote_player(&self) -> usize {
+        match self {
+            Role::Host => 1,
+            Role::Guest => 0,
+        }
+    }
+}
+
+/// What one player did before a tick: the directions that they entered and whether they held the
+/// boost input.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Input {
+    pub tick: u64,
+    pub directions: Vec<Direction>,
+    pub boosting: bool,
+}
+
+/// The most ticks that a client with rollback plays ahead of the input of the other player, with
+/// a guess for it.
+pub const ROLLBACK_WINDOW_TICKS: u64 = 5;
+
+/// Keeps the game of both clients in step, by playing every tick with the input of both players.
+///
+/// With rollback, the game doesn't wait for the other player. Instead, it guesses that the other
+/// player goes straight on, and plays the tick anyway. The game after the last tick with real
+/// input for both players is kept, so the guessed ticks can be played again once the real input
+/// arrives. Only the steps within a round are guessed, so a crash is never shown before it is
+/// certain.
+#[derive(Clone, Debug)]
+pub struct Lockstep {
+    pub role: Role,
+    /// The number of ticks played so far, including the ticks with a guessed input.
+    pub tick: u64,
+    /// The number of ticks that were played with the real input of both players.
+    confirmed_tick: u64,
+    /// With rollback, the game after the ticks with real input. Without rollback, the game itself
+    /// only ever has real input, so this stays `None`.
+    confirmed: Option<GameState>,
+    rollback: bool,
+    /// Inputs of the local player from the first tick without real input of both players on.
+    local: VecDeque<Input>,
+    /// Inputs of the remote player that arrived, from the first tick that isn't confirmed on.
+    remote: VecDeque<Input>,
+    /// Whether the remote player held the boost input the last time that we heard, which is
+    /// the guess for as long as there is no new input.
+    remote_boosting: bool,
+}
+
+impl Lockstep {
+    /// Start at the first tick. Nobody can have entered anything for the ticks within the input
+    /// delay, so those inputs are empty on both clients.
+    pub fn new(role: Role, rollback: bool) -> Self {
+        let empty: VecDeque<Input> = (0..INPUT_DELAY_TICKS)
+            .map(|tick| Input {
+                tick,
+                ..Input::default()
+            })
+            .collect();
+
+        Lockstep {
+            role,
+            tick: 0,
+            confirmed_tick: 0,
+            confirmed: None,
+            rollback,
+            local: empty.clone(),
+            remote: empty,
+            remote_boosting: false,
+        }
+    }
+
+    /// Take what the local player entered since the last call, as the input for the tick after the
+    /// input delay. Returns the inputs to send, which are none if the input for that tick was
+    /// already taken, so it is sent only once, even while waiting for the other player. After a
+    /// rollback skipped ahead, the ticks in between get an empty input.
+    pub fn take_local_input(&mut self, game_state: &mut GameState) -> Vec<Input> {
+        let target = self.tick + INPUT_DELAY_TICKS;
+        let next = self.local.back().map_or(self.confirmed_tick, |input| input.tick + 1);
+
+        let player = &mut game_state.players[self.role.local_player()];
+        let mut directions: Vec<Direction> = player.pending_directions.drain(..).collect();
+        let inputs: Vec<Input> = (next..=target)
+            .map(|tick| Input {
+                tick,
+                directions: std::mem::take(&mut directions),
+                boosting: player.boosting,
+            })
+            .collect();
+
+        // nothing was sent, so the directions still wait for the next tick
+        player.pending_directions.extend(directions);
+        self.local.extend(inputs.iter().cloned());
+        inputs
+    }
+
+    /// Keep an input of the other player until its tick is confirmed.
+    pub fn receive(&mut self, input: Input) {
+        self.remote_boosting = input.boosting;
+        self.remote.push_back(input);
+    }
+
+    /// Play the next tick, if the input of both players for it is there, or with rollback, if the
+    /// game isn't too far ahead of the other player. Returns whether the game changed.
+    pub fn advance(&mut self, game_state: &mut GameState) -> bool {
+        // what the local player entered after the last input was taken belongs to a later tick
+        let local_player = self.role.local_player();
+        let entered = std::mem::take(&mut game_state.players[local_player].pending_directions);
+        let held = game_state.players[local_player].boosting;
+
+        let changed = if self.rollback {
+            self.advance_with_rollback(game_state)
+        } else {
+            self.advance_confirmed(game_state)
+        };
+
+        let player = &mut game_state.players[local_player];
+        player.pending_directions = entered;
+        player.boosting = held;
+        changed
+    }
+
+    /// Without rollback, play the next tick only when the input of both players is there.
+    fn advance_confirmed(&mut self, game_state: &mut GameState) -> bool {
+        let Some((local, remote)) = self.confirm_next() else {
+            return false;
+        };
+
+        play_tick(game_state, self.role, &local, &remote);
+        self.tick += 1;
+        true
+    }
+
+    fn advance_with_rollback(&mut self, game_state: &mut GameState) -> bool {
+        let mut confirmed = self.confirmed.take().unwrap_or_else(|| game_state.clone());
+        let target = self.tick;
+        let mut has_confirmed = false;
+        // the input within the input delay is known early, but its ticks are played one at a time
+        while self.confirmed_tick <= target {
+            let Some((local, remote)) = self.confirm_next() else {
+                break;
+            };
+            play_tick(&mut confirmed, self.role, &local, &remote);
+            has_confirmed = true;
+        }
+
+        if has_confirmed {
+            // play the guessed ticks again, now that more of the real input is known
+            *game_state = confirmed.clone();
+            self.tick = self.confirmed_tick;
+            while self.tick < target && self.play_guess(game_state) {}
+        }
+        self.confirmed = Some(confirmed);
+
+        let is_in_window = self.tick < self.confirmed_tick + ROLLBACK_WINDOW_TICKS;
+        let can_guess = self.tick == target && is_in_window;
+        (can_guess && self.play_guess(game_state)) || has_confirmed
+    }
+
+    /// Take the inputs of the first tick that isn't confirmed yet, if they are both there.
+    fn confirm_next(&mut self) -> Option<(Input, Input)> {
+        let is_ready = |inputs: &VecDeque<Input>| {
+            inputs.front().is_some_and(|input| input.tick == self.confirmed_tick)
+        };
+        if !is_ready(&self.local) || !is_ready(&self.remote) {
+            return None;
+        }
+
+        self.confirmed_tick += 1;
+        Some((self.local.pop_front()?, self.remote.pop_front()?))
+    }
+
+    /// Play the next tick with the local input and a guess for the remote input. Returns `false`
+    /// without playing if there is no local input yet, or if the tick is not a step within a
+    /// round, or would end it.
+    fn play_guess(&mut self, game_state: &mut GameState) -> bool {
+        let offset = (self.tick - self.confirmed_tick) as usize;
+        let Some(local) = self.local.get(offset) else {
+            return false;
+        };
+        if game_state.phase != Phase::Step {
+            return false;
+        }
+
+        let guess = Input {
+            tick: self.tick,
+            directions: vec![],
+            boosting: self.remote_boosting,
+        };
+        let mut guessed = game_state.clone();
+        play_tick(&mut guessed, self.role, local, &guess);
+        if guessed.phase != Phase::Step {
+            return false;
+        }
+
+        *game_state = guessed;
+        self.tick += 1;
+        true
+    }
+}
+
+/// Play one tick of the game with the specified input for both players.
+fn play_tick(game_state: &mut GameState, role: Role, local: &Input, remote: &Input) {
+    for (index, input) in [(role.local_player(), local), (role.remote_player(), remote)] {
+        let player = &mut game_state.players[index];
+        player.pending_directions.clear();
+        for direction in &input.directions {
+            player.set_direction(*direction);
+        }
+        player.boosting = input.boosting;
+    }
+
+    game_state.tick();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bot::BotDifficulty;
+    use crate::common::Color;
+    use crate::rng::Rng;
+
+    fn online_game() -> GameState {
+        let colors = [Color::red(), Color::blue()];
+        let mut game_state = GameState::new(2, 2, BotDifficulty::Easy, 3, 16, 12, &colors);
+        game_state.set_seed(316);
+        game_state
+    }
+
+    /// The game as JSON, without the directions that wait in the queue of the players. Every tick
+    /// starts with only the directions of its input, so they don't make a difference to the game.
+    fn to_json(game_state: &GameState) -> String {
+        let mut game_state = game_state.clone();
+        for player in &mut game_state.players {
+            player.pending_directions.clear();
+        }
+        game_state.to_json()
+    }
+
+    /// One side of an online game, with every state that its game was in.
+    struct Client {
+        lockstep: Lockstep,
+        game_state: GameState,
+        /// The inputs that this client sent, with the frame on which they arrive at the other.
+        sent: Vec<(usize, Input)>,
+        /// After every frame: the tick, the confirmed tick and the game as JSON.
+        history: Vec<(u64, u64, String)>,
+    }
+
+    /// Play an online game between a host and a guest for the specified number of frames, over a
+    /// network that delivers every input `latency` frames after it was sent. On every frame,
+    /// both players may enter a random direction.
+    fn play_online(rollback: bool, latency: usize, frames: usize) -> [Client; 2] {
+        let game_state = online_game();
+        let mut clients = [Role::Host, Role::Guest].map(|role| Client {
+            lockstep: Lockstep::new(role, rollback),
+            game_state: game_state.clone(),
+            sent: vec![],
+            history: vec![],
+        });
+
+        let mut rng = Rng::seeded(316);
+        for frame in 0..frames {
+            for client in &mut clients {
+                let player = &mut client.game_state.players[client.lockstep.role.local_player()];
+                if rng.below(4) == 0 {
+                    player.pending_directions.push_back(Direction::ALL[rng.below(4)]);
+                }
+
+                let inputs = client.lockstep.take_local_input(&mut client.game_state);
+                client.sent.extend(inputs.into_iter().map(|input| (frame + latency, input)));
+            }
+
+            for (to, from) in [(0, 1), (1, 0)] {
+                let arrived: Vec<Input> = clients[from]
+                    .sent
+                    .iter()
+                    .filter(|(arrival, _)| *arrival == frame)
+                    .map(|(_, input)| input.clone())
+                    .collect();
+                for input in arrived {
+                    clients[to].lockstep.receive(input);
+                }
+            }
+
+            for client in &mut clients {
+                client.lockstep.advance(&mut client.game_state);
+                let Lockstep { tick, confirmed_tick, .. } = client.lockstep;
+                client.history.push((tick, confirmed_tick, to_json(&client.game_state)));
+            }
+        }
+
+        clients
+    }
+
+    /// The game as JSON after every tick, played with the inputs that both clients sent, as if
+    /// both players were at the same computer.
+    fn play_offline(clients: &[Client; 2]) -> Vec<String> {
+        let [host, guest] = clients.each_ref().map(|client| {
+            let inputs = client.sent.iter().map(|(_, input)| input.clone());
+            let empty = (0..INPUT_DELAY_TICKS).map(|tick| Input {
+                tick,
+                ..Input::default()
+            });
+            let inputs: Vec<Input> = empty.chain(inputs).collect();
+            assert!(inputs.iter().enumerate().all(|(tick, input)| input.tick == tick as u64));
+            inputs
+        });
+
+        let mut game_state = online_game();
+        let mut games = vec![to_json(&game_state)];
+        for (local, remote) in host.iter().zip(&guest) {
+            play_tick(&mut game_state, Role::Host, local, remote);
+            games.push(to_json(&game_state));
+        }
+        games
+    }
+
+    #[test]
+    fn ticks_within_the_input_delay_are_played_without_waiting() {
+        let mut game_state = online_game();
+        let mut lockstep = Lockstep::new(Role::Guest, false);
+
+        for tick in 0..INPUT_DELAY_TICKS {
+            assert!(lockstep.advance(&mut game_state), "tick {}", tick);
+        }
+        assert!(!lockstep.advance(&mut game_state));
+        assert_eq!(lockstep.tick, INPUT_DELAY_TICKS);
+    }
+
+    #[test]
+    fn local_input_is_taken_once_for_the_tick_after_the_input_delay() {
+        let mut game_state = online_game();
+        let mut lockstep = Lockstep::new(Role::Host, false);
+
+        let player = &mut game_state.players[0];
+        player.pending_directions.push_back(Direction::East);
+        player.boosting = true;
+        let expected = Input {
+            tick: INPUT_DELAY_TICKS,
+            directions: vec![Direction::East],
+            boosting: true,
+        };
+        assert_eq!(lockstep.take_local_input(&mut game_state), vec![expected]);
+        assert!(game_state.players[0].pending_directions.is_empty());
+
+        // until the game moves on, what the player enters waits for the next tick
+        game_state.players[0].pending_directions.push_back(Direction::North);
+        assert_eq!(lockstep.take_local_input(&mut game_state), vec![]);
+        assert_eq!(game_state.players[0].pending_directions, [Direction::North]);
+    }
+
+    #[test]
+    fn tick_is_confirmed_once_the_input_of_both_players_is_there() {
+        let mut game_state = online_game();
+        let mut lockstep = Lockstep::new(Role::Host, false);
+        for _ in 0..INPUT_DELAY_TICKS {
+            assert!(lockstep.confirm_next().is_some());
+        }
+        assert!(lockstep.confirm_next().is_none());
+
+        let remote = Input {
+            tick: INPUT_DELAY_TICKS,
+            directions: vec![],
+            boosting: true,
+        };
+        lockstep.receive(remote.clone());
+        assert!(lockstep.confirm_next().is_none());
+
+        let local = lockstep.take_local_input(&mut game_state).remove(0);
+        assert_eq!(lockstep.confirm_next(), Some((local, remote)));
+        assert_eq!(lockstep.confirmed_tick, INPUT_DELAY_TICKS + 1);
+    }
+
+    #[test]
+    fn both_clients_play_the_same_game_as_without_a_network() {
+        for latency in [0, 1, 4] {
+            let clients = play_online(false, latency, 300);
+            let games = play_offline(&clients);
+
+            for client in &clients {
+                assert!(client.lockstep.tick >= 100, "latency {}", latency);
+                for (tick, confirmed_tick, game) in &client.history {
+                    assert_eq!(tick, confirmed_tick, "latency {}", latency);
+                    assert_eq!(game, &games[*tick as usize], "latency {}, tick {}", latency, tick);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rollback_plays_the_same_game_as_lockstep_with_delayed_input() {
+        for latency in [1, 3, 8] {
+            let clients = play_online(true, latency, 300);
+            let games = play_offline(&clients);
+
+            for client in &clients {
+                let mut max_ahead = 0;
+                let mut wrong_guesses = 0;
+                let mut caught_up = 0;
+                let mut last_tick = 0;
+                for (tick, confirmed_tick, game) in &client.history {
+                    let ahead = tick - confirmed_tick;
+                    assert!(ahead <= ROLLBACK_WINDOW_TICKS, "latency {}, tick {}", latency, tick);
+                    assert!(*tick <= last_tick + 1, "latency {}, tick {}", latency, tick);
+                    last_tick = *tick;
+
+                    // once the real input is there, the guessed ticks are played again
+                    if ahead == 0 {
+                        let expected = &games[*tick as usize];
+                        assert_eq!(game, expected, "latency {}, tick {}", latency, tick);
+                        if max_ahead > 0 {
+                            caught_up += 1;
+                        }
+                    } else if games.get(*tick as usize) != Some(game) {
+                        wrong_guesses += 1;
+                    }
+                    max_ahead = max_ahead.max(ahead);
+                }
+
+                let confirmed = client.lockstep.confirmed.as_ref().unwrap();
+                assert_eq!(to_json(confirmed), games[client.lockstep.confirmed_tick as usize]);
+                // the input delay hides a short latency, so there is nothing to guess
+                if latency > INPUT_DELAY_TICKS as usize {
+                    assert!(wrong_guesses > 0 && caught_up > 0, "latency {}", latency);
+                } else {
+                    assert_eq!(max_ahead, 0, "latency {}", latency);
+                }
+                if latency > ROLLBACK_WINDOW_TICKS as usize {
+                    assert_eq!(max_ahead, ROLLBACK_WINDOW_TICKS, "latency {}", latency);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,145 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use serde::{Deserialize, Serialize};
+
+/// A small pseudo-random number generator (SplitMix64). It is seedable, so that games and bot
+/// decisions can be reproduced exactly, and it is part of the game state, so that saved games and
+/// replays continue with the same random numbers.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Create a generator that always produces the same numbers for the same seed.
+    pub fn seeded(seed: u64) -> Self {
+        Rng { state: seed }
+    }
+
+    /// Create a generator with an unpredictable seed. With the `js` feature in the browser, the
+    /// seed comes from `Math.random`; otherwise it comes from the system clock.
+    pub fn from_entropy() -> Self {
+        Rng::seeded(entropy())
+    }
+
+    /// The next random number, from the whole range of `u64`.
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A random number between 0.0 (inclusive) and 1.0 (exclusive), like `Math.random`.
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// A random index between 0 (inclusive) and `n` (exclusive).
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_f64() * n as f64) as usize
+    }
+}
+
+#[cfg(all(feature = "js", target_arch = "wasm32"))]
+fn entropy() -> u64 {
+    use js_sys::Math;
+
+    let high = (Math::random() * u32::MAX as f64) as u64;
+    let low = (Math::random() * u32::MAX as f64) as u64;
+    (high << 32) | low
+}
+
+#[cfg(not(all(feature = "js", target_arch = "wasm32")))]
+fn entropy() -> u64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_gives_same_numbers() {
+        let mut a = Rng::seeded(42);
+        let mut b = Rng::seeded(42);
+
+        for _ in 0..100 {
+            assert_eq!(a.next_u64(), b.next_u64());
+        }
+    }
+
+    #[test]
+    fn different_seeds_give_different_numbers() {
+        let mut a = Rng::seeded(1);
+        let mut b = Rng::seeded(2);
+        let a: Vec<u64> = (0..10).map(|_| a.next_u64()).collect();
+        let b: Vec<u64> = (0..10).map(|_| b.next_u64()).collect();
+
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn clone_continues_with_the_same_numbers() {
+        let mut rng = Rng::seeded(7);
+        rng.next_u64();
+        let mut cloned = rng.clone();
+
+        assert_eq!(rng.next_u64(), cloned.next_u64());
+    }
+
+    #[test]
+    fn next_f64_is_between_zero_and_one() {
+        let mut rng = Rng::seeded(3);
+
+        for _ in 0..10_000 {
+            let value = rng.next_f64();
+            assert!((0.0..1.0).contains(&value), "{} is out of range", value);
+        }
+    }
+
+    #[test]
+    fn below_stays_in_range_and_reaches_every_value() {
+        let mut rng = Rng::seeded(5);
+
+        for n in [1, 2, 3, 10, 28] {
+            let mut seen = vec![false; n];
+            for _ in 0..1_000 {
+                let value = rng.below(n);
+                assert!(value < n, "{} is not below {}", value, n);
+                seen[value] = true;
+            }
+            assert!(seen.iter().all(|s| *s), "not every value below {} came up", n);
+        }
+    }
+
+    #[test]
+    fn below_zero_is_zero() {
+        let mut rng = Rng::seeded(9);
+
+        assert_eq!(rng.below(0), 0);
+    }
+}
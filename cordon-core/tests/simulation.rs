@@ -0,0 +1,265 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+//! Games that are played without a browser, to check the rules from start to finish.
+
+use cordon_core::{
+    bot::{self, BotDifficulty},
+    common::{Color, Direction},
+    game::{Controller, GameState, MovementMode, Phase, MAX_DRAWN_ROUNDS},
+};
+
+const WIDTH: usize = 16;
+const HEIGHT: usize = 12;
+
+/// A game of humans who don't press anything, so that every player drives straight ahead unless
+/// the test steers it.
+fn new_game(num_players: usize, max_score: u32) -> GameState {
+    let colors = [Color::red(), Color::blue(), Color::green(), Color::white()];
+    GameState::new(num_players, num_players, BotDifficulty::Easy, max_score, WIDTH, HEIGHT, &colors)
+}
+
+/// Tick through the countdown, until the players move.
+fn start_round(game_state: &mut GameState) {
+    while game_state.phase == Phase::Countdown {
+        game_state.tick();
+    }
+    assert_eq!(game_state.phase, Phase::Step);
+}
+
+/// Steer the first player into the border on its left, and tick until it crashes.
+fn crash_first_player(game_state: &mut GameState) {
+    let (head, _) = *game_state.players[0].segments.back().unwrap();
+    assert!(game_state.players[0].set_direction(Direction::West));
+
+    for _ in 0..=head.x * game_state.players.len() {
+        if game_state.phase != Phase::Step || game_state.players[0].eliminated {
+            break;
+        }
+        game_state.tick();
+    }
+}
+
+#[test]
+fn crashing_into_the_border_gives_the_other_player_a_point() {
+    let mut game_state = new_game(2, 3);
+    start_round(&mut game_state);
+
+    crash_first_player(&mut game_state);
+
+    assert_eq!(game_state.phase, Phase::Collision);
+    assert_eq!(game_state.crashed_players(), [0]);
+    let (head, _) = game_state.players[0].segments.back().unwrap();
+    assert_eq!(head.x, 0);
+    assert_eq!(game_state.players[0].score, 0);
+    assert_eq!(game_state.players[1].score, 1);
+}
+
+#[test]
+fn round_without_a_winner_goes_on_to_the_next_round() {
+    let mut game_state = new_game(2, 3);
+    start_round(&mut game_state);
+    crash_first_player(&mut game_state);
+
+    while game_state.phase == Phase::Collision {
+        game_state.tick();
+    }
+    assert_eq!(game_state.phase, Phase::Score);
+    assert!(!game_state.is_game_over());
+
+    game_state.tick();
+    assert_eq!(game_state.phase, Phase::Countdown);
+    assert!(game_state.players.iter().all(|p| p.segments.len() == 1));
+    assert_eq!(game_state.players[1].score, 1);
+}
+
+#[test]
+fn reaching_the_winning_score_ends_the_game() {
+    let mut game_state = new_game(2, 1);
+    assert_eq!(game_state.winning_score(), 1);
+    start_round(&mut game_state);
+    crash_first_player(&mut game_state);
+
+    while game_state.phase == Phase::Collision {
+        game_state.tick();
+    }
+
+    assert_eq!(game_state.phase, Phase::GameOver);
+    assert!(game_state.is_game_over());
+    assert_eq!(game_state.winner(), Some(1));
+    assert_eq!(game_state.tick_interval(), None);
+}
+
+#[test]
+fn the_others_play_on_after_a_crash_with_three_players() {
+    let mut game_state = new_game(3, 3);
+    start_round(&mut game_state);
+
+    crash_first_player(&mut game_state);
+
+    assert_eq!(game_state.phase, Phase::Step);
+    assert!(game_state.players[0].eliminated);
+    assert_eq!(game_state.remaining_players(), [1, 2]);
+    let scores: Vec<u32> = game_state.players.iter().map(|p| p.score).collect();
+    assert_eq!(scores, [0, 1, 1]);
+}
+
+#[test]
+fn last_player_standing_wins_with_three_players() {
+    let mut game_state = new_game(3, 1);
+    assert_eq!(game_state.winning_score(), 2);
+    start_round(&mut game_state);
+    crash_first_player(&mut game_state);
+
+    // the two others drive straight on until one of them crashes too
+    while game_state.phase == Phase::Step {
+        game_state.tick();
+    }
+    while game_state.phase == Phase::Collision {
+        game_state.tick();
+    }
+
+    assert_eq!(game_state.phase, Phase::GameOver);
+    let winner = game_state.winner().unwrap();
+    assert_ne!(winner, 0);
+    assert_eq!(game_state.players[winner].score, 2);
+    assert_eq!(game_state.placements()[0], winner);
+    assert_eq!(*game_state.placements().last().unwrap(), 0);
+}
+
+#[test]
+fn round_that_runs_out_of_time_goes_to_the_player_with_the_most_room() {
+    let mut game_state = new_game(2, 3);
+    game_state.max_round_ticks = Some(6);
+    start_round(&mut game_state);
+
+    // the first player turns towards the border, away from most of the arena
+    assert!(game_state.players[0].set_direction(Direction::West));
+    for _ in 0..6 {
+        assert_eq!(game_state.phase, Phase::Step);
+        game_state.tick();
+    }
+
+    assert_eq!(game_state.phase, Phase::Score);
+    assert!(game_state.crashed_players().is_empty());
+    let room = bot::territories(&game_state);
+    assert!(room[1] > room[0], "{:?}", room);
+    let scores: Vec<u32> = game_state.players.iter().map(|p| p.score).collect();
+    assert_eq!(scores, [0, 1]);
+}
+
+/// The most ticks that any of the bot games below may take. A win takes a few rounds of a few
+/// hundred ticks each, so this leaves plenty of room for rounds that end in a draw.
+const MAX_GAME_TICKS: usize = 100_000;
+
+/// A game of bots of the specified difficulties, starting from the specified seed.
+fn bot_game(bots: &[BotDifficulty], movement_mode: MovementMode, seed: u64) -> GameState {
+    let colors = [Color::red(), Color::blue(), Color::green(), Color::white()];
+    let mut game_state = GameState::new(0, bots.len(), bots[0], 3, WIDTH, HEIGHT, &colors);
+    for (player, bot) in game_state.players.iter_mut().zip(bots) {
+        player.controller = Controller::Bot(*bot);
+    }
+    game_state.movement_mode = movement_mode;
+    game_state.set_seed(seed);
+    game_state
+}
+
+/// Play a game to the end one tick at a time, checking that no score ever goes down, and return
+/// the number of ticks that it took.
+fn play_to_the_end(game_state: &mut GameState) -> usize {
+    let scores = |game_state: &GameState| -> Vec<u32> {
+        game_state.players.iter().map(|player| player.score).collect()
+    };
+
+    let mut ticks = 0;
+    while game_state.phase != Phase::GameOver {
+        assert!(ticks < MAX_GAME_TICKS, "the game is still going after {} ticks", ticks);
+
+        let before = scores(game_state);
+        game_state.tick();
+        ticks += 1;
+
+        let after = scores(game_state);
+        assert!(before.iter().zip(&after).all(|(b, a)| a >= b), "{:?} to {:?}", before, after);
+    }
+    ticks
+}
+
+#[test]
+fn bot_games_end_and_scores_never_go_down() {
+    use BotDifficulty::*;
+
+    // the last three lineups have bots that never pick randomly, which can crash in the same way
+    // in every round, so that no one scores
+    let lineups: [&[BotDifficulty]; 8] = [
+        &[Easy, Easy],
+        &[Medium, Easy],
+        &[Hunter, Easy],
+        &[Aggressor, Easy, Easy],
+        &[Easy, Medium, Easy, Hunter],
+        &[Hard, Hunter],
+        &[Hunter, Aggressor],
+        &[Hard, Hunter, Aggressor],
+    ];
+
+    for (seed, bots) in lineups.into_iter().enumerate() {
+        for movement_mode in [MovementMode::RoundRobin, MovementMode::Simultaneous] {
+            let mut game_state = bot_game(bots, movement_mode, seed as u64);
+            play_to_the_end(&mut game_state);
+
+            match game_state.winner() {
+                Some(winner) => {
+                    assert!(game_state.players[winner].score >= game_state.winning_score());
+                }
+                None => assert_eq!(game_state.drawn_rounds, MAX_DRAWN_ROUNDS, "{:?}", bots),
+            }
+        }
+    }
+}
+
+#[test]
+fn bots_that_keep_drawing_end_the_game_as_a_draw() {
+    // when these bots move at the same time, they crash together in every round
+    let bots = [BotDifficulty::Hunter, BotDifficulty::Aggressor];
+    let mut game_state = bot_game(&bots, MovementMode::Simultaneous, 303);
+    play_to_the_end(&mut game_state);
+
+    assert!(game_state.is_draw());
+    assert_eq!(game_state.winner(), None);
+    assert_eq!(game_state.drawn_rounds, MAX_DRAWN_ROUNDS);
+    assert!(game_state.players.iter().all(|player| player.score == 0));
+}
+
+#[test]
+fn tick_n_plays_the_same_game_as_ticking_one_at_a_time() {
+    let bots = [BotDifficulty::Medium, BotDifficulty::Hunter];
+
+    let mut ticked = bot_game(&bots, MovementMode::RoundRobin, 330);
+    let ticks = play_to_the_end(&mut ticked);
+
+    let mut fast_forwarded = bot_game(&bots, MovementMode::RoundRobin, 330);
+    fast_forwarded.tick_n(ticks);
+    assert_eq!(fast_forwarded.phase, Phase::GameOver);
+    assert_eq!(fast_forwarded.to_json(), ticked.to_json());
+
+    // once the game is over, more ticks don't change anything
+    fast_forwarded.tick_n(1000);
+    assert_eq!(fast_forwarded.to_json(), ticked.to_json());
+}
+
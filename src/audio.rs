@@ -0,0 +1,163 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::cell::{Cell, RefCell};
+
+use web_sys::{AudioContext, AudioNode, HtmlAudioElement, OscillatorType};
+
+use cordon_core::rng::Rng;
+
+/// How long the explosion noise lasts, in seconds.
+const CRASH_DURATION: f64 = 0.4;
+
+thread_local! {
+    static CONTEXT: RefCell<Option<AudioContext>> = const { RefCell::new(None) };
+    static MUTED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// The sound effects of the game.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Sound {
+    /// A short blip when a player changes direction.
+    Turn,
+    /// A burst of noise when a player crashes.
+    Crash,
+    /// A rising chime when the points are handed out.
+    Score,
+}
+
+/// Whether the sound effects are muted.
+pub fn is_muted() -> bool {
+    MUTED.get()
+}
+
+/// Mute or unmute the sound effects.
+pub fn set_muted(muted: bool) {
+    MUTED.set(muted);
+}
+
+/// An audio file that plays over and over, for background music.
+pub struct MusicPlayer {
+    element: HtmlAudioElement,
+}
+
+impl MusicPlayer {
+    /// Load the audio file at the specified URL. It doesn't start playing until `play` is called.
+    pub fn new(src: &str) -> Option<Self> {
+        let element = HtmlAudioElement::new_with_src(src).ok()?;
+        element.set_loop(true);
+        Some(MusicPlayer { element })
+    }
+
+    /// Start playing, or continue where the music was stopped. Browsers refuse this until the
+    /// user has interacted with the page.
+    pub fn play(&self) {
+        if self.element.paused() {
+            let _ = self.element.play();
+        }
+    }
+
+    pub fn stop(&self) {
+        let _ = self.element.pause();
+    }
+
+    /// Change the volume, from 0.0 (silent) to 1.0 (loudest).
+    pub fn set_volume(&self, volume: f64) {
+        self.element.set_volume(volume.clamp(0.0, 1.0));
+    }
+}
+
+/// Play the specified sound, unless the sound effects are muted. Browsers only allow audio after
+/// the user has interacted with the page, so the audio context is created on first use.
+pub fn play(sound: Sound) {
+    if is_muted() {
+        return;
+    }
+
+    CONTEXT.with_borrow_mut(|context| {
+        if context.is_none() {
+            *context = AudioContext::new().ok();
+        }
+
+        if let Some(context) = context {
+            // errors only mean that the sound isn't heard, which is not worth interrupting for
+            let _ = match sound {
+                Sound::Turn => play_tone(context, OscillatorType::Square, 440.0, 0.0, 0.05),
+                Sound::Crash => play_noise(context),
+                Sound::Score => play_tone(context, OscillatorType::Sine, 660.0, 0.0, 0.15)
+                    .and_then(|_| play_tone(context, OscillatorType::Sine, 880.0, 0.12, 0.25)),
+            };
+        }
+    });
+}
+
+/// Play a tone of the specified frequency in Hz, starting after `delay` seconds and fading out
+/// over `duration` seconds.
+fn play_tone(
+    context: &AudioContext,
+    oscillator_type: OscillatorType,
+    frequency: f32,
+    delay: f64,
+    duration: f64,
+) -> Result<(), web_sys::wasm_bindgen::JsValue> {
+    let start = context.current_time() + delay;
+    let oscillator = context.create_oscillator()?;
+    oscillator.set_type(oscillator_type);
+    oscillator.frequency().set_value_at_time(frequency, start)?;
+
+    let gain = fade_out(context, start, duration)?;
+    oscillator.connect_with_audio_node(&gain)?;
+    oscillator.start_with_when(start)?;
+    oscillator.stop_with_when(start + duration)?;
+    Ok(())
+}
+
+/// Play a burst of random noise that fades out, which sounds like an explosion.
+fn play_noise(context: &AudioContext) -> Result<(), web_sys::wasm_bindgen::JsValue> {
+    let sample_rate = context.sample_rate();
+    let length = (sample_rate as f64 * CRASH_DURATION) as u32;
+    let mut rng = Rng::from_entropy();
+    let samples: Vec<f32> = (0..length).map(|_| rng.next_f64() as f32 * 2.0 - 1.0).collect();
+
+    let buffer = context.create_buffer(1, length, sample_rate)?;
+    buffer.copy_to_channel(&samples, 0)?;
+
+    let start = context.current_time();
+    let source = context.create_buffer_source()?;
+    source.set_buffer(Some(&buffer));
+
+    let gain = fade_out(context, start, CRASH_DURATION)?;
+    source.connect_with_audio_node(&gain)?;
+    source.start_with_when(start)?;
+    Ok(())
+}
+
+/// Create a volume control at the output that starts fading out at `start`, until it is silent
+/// after `duration` seconds.
+fn fade_out(
+    context: &AudioContext,
+    start: f64,
+    duration: f64,
+) -> Result<AudioNode, web_sys::wasm_bindgen::JsValue> {
+    let gain = context.create_gain()?;
+    gain.gain().set_value_at_time(0.2, start)?;
+    gain.gain().exponential_ramp_to_value_at_time(0.001, start + duration)?;
+    gain.connect_with_audio_node(&context.destination())?;
+    Ok(gain.into())
+}
@@ -0,0 +1,172 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use serde::{Deserialize, Serialize};
+
+use cordon_core::{
+    common::{Direction, KeyMap, ARROW_KEYS, IJKL_KEYS, TFGH_KEYS, WASD_KEYS},
+    game::{Controller, HUMAN_CONTROLLERS},
+};
+
+/// Keys that already do something else, like pausing or clearing the trail in practice, so they
+/// can't steer a player.
+const RESERVED_KEYS: [&str; 3] = ["Escape", "p", "r"];
+
+/// The keys that steer the players who use the keyboard.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct KeyBindings {
+    pub wasd: Vec<(Direction, String)>,
+    pub arrows: Vec<(Direction, String)>,
+    #[serde(default = "default_ijkl_keys")]
+    pub ijkl: Vec<(Direction, String)>,
+    #[serde(default = "default_tfgh_keys")]
+    pub tfgh: Vec<(Direction, String)>,
+    /// The key that each controller holds down to boost.
+    #[serde(default = "default_boost_keys")]
+    pub boost: Vec<(Controller, String)>,
+}
+
+fn default_boost_keys() -> Vec<(Controller, String)> {
+    vec![
+        (Controller::Wasd, " ".to_string()),
+        (Controller::Arrows, "Enter".to_string()),
+        (Controller::Ijkl, "u".to_string()),
+        (Controller::Tfgh, "v".to_string()),
+    ]
+}
+
+fn to_bindings(keys: &KeyMap) -> Vec<(Direction, String)> {
+    keys.iter().map(|(direction, key)| (*direction, key.to_string())).collect()
+}
+
+fn default_ijkl_keys() -> Vec<(Direction, String)> {
+    to_bindings(&IJKL_KEYS)
+}
+
+fn default_tfgh_keys() -> Vec<(Direction, String)> {
+    to_bindings(&TFGH_KEYS)
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        KeyBindings {
+            wasd: to_bindings(&WASD_KEYS),
+            arrows: to_bindings(&ARROW_KEYS),
+            ijkl: default_ijkl_keys(),
+            tfgh: default_tfgh_keys(),
+            boost: default_boost_keys(),
+        }
+    }
+}
+
+impl KeyBindings {
+    fn bindings(&self, controller: &Controller) -> &[(Direction, String)] {
+        match controller {
+            Controller::Wasd => &self.wasd,
+            Controller::Arrows => &self.arrows,
+            Controller::Ijkl => &self.ijkl,
+            Controller::Tfgh => &self.tfgh,
+            Controller::Gamepad(_) | Controller::Bot(_) | Controller::Remote => &[],
+        }
+    }
+
+    /// The direction in which the specified key steers a player with the specified controller.
+    pub fn direction(&self, controller: &Controller, key: &str) -> Option<Direction> {
+        let key = normalize(key);
+        self.bindings(controller)
+            .iter()
+            .find(|(_, k)| *k == key)
+            .map(|(direction, _)| *direction)
+    }
+
+    /// Check whether the specified key boosts a player with the specified controller.
+    pub fn is_boost_key(&self, controller: &Controller, key: &str) -> bool {
+        let key = normalize(key);
+        self.boost.iter().any(|(c, k)| c == controller && *k == key)
+    }
+
+    /// Check whether the specified key steers or boosts any player on the keyboard. While a game is
+    /// on, these keys shouldn't do what the browser would do with them, like scrolling the page.
+    pub fn is_game_key(&self, key: &str) -> bool {
+        HUMAN_CONTROLLERS.iter().any(|controller| {
+            self.direction(controller, key).is_some() || self.is_boost_key(controller, key)
+        })
+    }
+
+    /// The key that steers a player with the specified controller in the specified direction.
+    pub fn key(&self, controller: &Controller, direction: Direction) -> Option<&str> {
+        self.bindings(controller)
+            .iter()
+            .find(|(d, _)| *d == direction)
+            .map(|(_, key)| key.as_str())
+    }
+
+    /// Let the specified key steer a player with the specified controller in the specified
+    /// direction. Returns `false` without changing anything if the key is reserved, if it boosts a
+    /// player, or if it already steers any player in another direction.
+    pub fn bind(&mut self, controller: &Controller, direction: Direction, key: &str) -> bool {
+        let key = normalize(key);
+        let in_use = HUMAN_CONTROLLERS.iter().any(|c| {
+            self.bindings(c)
+                .iter()
+                .any(|(d, k)| *k == key && (c != controller || *d != direction))
+        });
+
+        let is_boost_key = self.boost.iter().any(|(_, k)| *k == key);
+
+        if in_use || is_boost_key || RESERVED_KEYS.contains(&key.as_str()) {
+            return false;
+        }
+
+        let bindings = match controller {
+            Controller::Wasd => &mut self.wasd,
+            Controller::Arrows => &mut self.arrows,
+            Controller::Ijkl => &mut self.ijkl,
+            Controller::Tfgh => &mut self.tfgh,
+            Controller::Gamepad(_) | Controller::Bot(_) | Controller::Remote => return false,
+        };
+
+        match bindings.iter_mut().find(|(d, _)| *d == direction) {
+            Some((_, k)) => *k = key,
+            None => bindings.push((direction, key)),
+        }
+        true
+    }
+}
+
+/// The name of a key, as it is shown in the settings.
+pub fn key_name(key: &str) -> String {
+    match key {
+        " " => "Space".to_string(),
+        "ArrowUp" => "↑".to_string(),
+        "ArrowDown" => "↓".to_string(),
+        "ArrowLeft" => "←".to_string(),
+        "ArrowRight" => "→".to_string(),
+        _ => key.to_uppercase(),
+    }
+}
+
+/// Letters are stored in lower case, so that the same key works with Shift or Caps Lock.
+fn normalize(key: &str) -> String {
+    if key.chars().count() == 1 {
+        key.to_lowercase()
+    } else {
+        key.to_string()
+    }
+}
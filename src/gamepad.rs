@@ -0,0 +1,98 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use web_sys::{wasm_bindgen::JsCast, Gamepad, GamepadButton};
+
+use cordon_core::common::Direction;
+
+/// How far an analog stick must be pushed before it counts as a direction, between 0 and 1.
+const DEADZONE: f64 = 0.5;
+
+/// The buttons of the d-pad in the standard gamepad mapping, with their directions.
+const DPAD_BUTTONS: [(u32, Direction); 4] = [
+    (12, Direction::North),
+    (13, Direction::South),
+    (14, Direction::West),
+    (15, Direction::East),
+];
+
+/// The button that boosts the player in the standard gamepad mapping: the bottom face button.
+const BOOST_BUTTON: u32 = 0;
+
+fn gamepads() -> Vec<Gamepad> {
+    let Some(gamepads) = web_sys::window().and_then(|w| w.navigator().get_gamepads().ok()) else {
+        return vec![];
+    };
+
+    // disconnected gamepads leave an empty slot in the list
+    gamepads
+        .iter()
+        .filter_map(|gamepad| gamepad.dyn_into::<Gamepad>().ok())
+        .filter(|gamepad| gamepad.connected())
+        .collect()
+}
+
+/// The indices of all connected gamepads.
+pub fn connected() -> Vec<usize> {
+    gamepads().iter().map(|gamepad| gamepad.index() as usize).collect()
+}
+
+/// Read the direction in which the gamepad with the specified index is pointed, using the d-pad
+/// or the left stick. Returns `None` if the gamepad isn't connected or isn't pointed anywhere.
+pub fn direction(index: usize) -> Option<Direction> {
+    let gamepad = gamepads().into_iter().find(|g| g.index() as usize == index)?;
+    let buttons = gamepad.buttons();
+
+    for (button, direction) in DPAD_BUTTONS {
+        let pressed = buttons
+            .get(button)
+            .dyn_into::<GamepadButton>()
+            .is_ok_and(|b| b.pressed());
+
+        if pressed {
+            return Some(direction);
+        }
+    }
+
+    let axes = gamepad.axes();
+    let x = axes.get(0).as_f64().unwrap_or(0.0);
+    let y = axes.get(1).as_f64().unwrap_or(0.0);
+
+    if x.abs().max(y.abs()) < DEADZONE {
+        None
+    } else if x.abs() > y.abs() {
+        Some(if x < 0.0 { Direction::West } else { Direction::East })
+    } else {
+        Some(if y < 0.0 { Direction::North } else { Direction::South })
+    }
+}
+
+/// Check whether the boost button of the gamepad with the specified index is held down.
+pub fn is_boost_pressed(index: usize) -> bool {
+    gamepads()
+        .into_iter()
+        .find(|g| g.index() as usize == index)
+        .is_some_and(|gamepad| {
+            gamepad
+                .buttons()
+                .get(BOOST_BUTTON)
+                .dyn_into::<GamepadButton>()
+                .is_ok_and(|b| b.pressed())
+        })
+}
@@ -0,0 +1,214 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use std::collections::HashMap;
+
+/// The number of bits in a pixel of an image, which gives a color table of 256 colors.
+const COLOR_BITS: u8 = 8;
+
+/// The number of levels of red, green and blue in the color table, which leaves room for all
+/// combinations of them in the 256 colors.
+const LEVELS: u32 = 6;
+
+/// The largest code that LZW compression can use in a GIF image.
+const MAX_CODE: u16 = 4095;
+
+/// Encodes an animated GIF image that repeats forever, one frame at a time. All frames share one
+/// color table with an even spread of colors, which is close enough for the few colors of the
+/// board. Every frame only stores the part that changed since the frame before it.
+pub struct GifEncoder {
+    width: u16,
+    height: u16,
+    bytes: Vec<u8>,
+    previous: Option<Vec<u8>>,
+}
+
+impl GifEncoder {
+    pub fn new(width: u16, height: u16) -> Self {
+        let mut bytes = b"GIF89a".to_vec();
+        bytes.extend(width.to_le_bytes());
+        bytes.extend(height.to_le_bytes());
+        // a global color table of 256 colors, without a background color or aspect ratio
+        bytes.extend([0xf0 | (COLOR_BITS - 1), 0, 0]);
+        for i in 0..1 << COLOR_BITS {
+            bytes.extend(table_color(i));
+        }
+        // the application extension that makes the animation repeat forever
+        bytes.extend([0x21, 0xff, 0x0b]);
+        bytes.extend(b"NETSCAPE2.0");
+        bytes.extend([0x03, 0x01, 0x00, 0x00, 0x00]);
+
+        GifEncoder {
+            width,
+            height,
+            bytes,
+            previous: None,
+        }
+    }
+
+    /// Add a frame that is shown for `delay_ms` milliseconds. The pixels are given as red, green,
+    /// blue and alpha bytes, row by row from the top left, like the data of a canvas.
+    pub fn add_frame(&mut self, rgba: &[u8], delay_ms: u32) {
+        let width = usize::from(self.width);
+        let pixels: Vec<u8> = rgba.chunks_exact(4).map(|p| color_index(p[0], p[1], p[2])).collect();
+
+        let (left, top, right, bottom) = match &self.previous {
+            None => (0, 0, width, usize::from(self.height)),
+            Some(previous) => changed_area(previous, &pixels, width),
+        };
+
+        // browsers show frames that are shorter than 20 ms for 100 ms instead
+        let delay = (delay_ms.div_ceil(10)).clamp(2, u32::from(u16::MAX)) as u16;
+        // the frame is drawn over the one before it, so the unchanged parts stay visible
+        self.bytes.extend([0x21, 0xf9, 0x04, 0x04]);
+        self.bytes.extend(delay.to_le_bytes());
+        self.bytes.extend([0x00, 0x00]);
+
+        self.bytes.push(0x2c);
+        for value in [left, top, right - left, bottom - top] {
+            self.bytes.extend((value as u16).to_le_bytes());
+        }
+        self.bytes.push(0x00);
+
+        let area = (top..bottom).flat_map(|y| &pixels[y * width + left..y * width + right]);
+        self.bytes.push(COLOR_BITS);
+        for block in compress(area.copied()).chunks(255) {
+            self.bytes.push(block.len() as u8);
+            self.bytes.extend(block);
+        }
+        self.bytes.push(0x00);
+
+        self.previous = Some(pixels);
+    }
+
+    /// The bytes of the whole image.
+    pub fn finish(mut self) -> Vec<u8> {
+        self.bytes.push(0x3b);
+        self.bytes
+    }
+}
+
+/// The color at the specified index of the color table. The rest are black, after all combinations of
+/// the levels of red, green and blue.
+fn table_color(index: u32) -> [u8; 3] {
+    if index >= LEVELS.pow(3) {
+        return [0, 0, 0];
+    }
+    let level = |i: u32| (i * 255 / (LEVELS - 1)) as u8;
+    [level(index / (LEVELS * LEVELS)), level(index / LEVELS % LEVELS), level(index % LEVELS)]
+}
+
+/// The index of the color in the color table that is closest to the specified color.
+fn color_index(r: u8, g: u8, b: u8) -> u8 {
+    let level = |c: u8| (u32::from(c) * (LEVELS - 1) + 127) / 255;
+    (level(r) * LEVELS * LEVELS + level(g) * LEVELS + level(b)) as u8
+}
+
+/// The smallest rectangle around all pixels that differ between the two frames, as the left, top,
+/// right and bottom edges. A frame without any changes still needs a pixel to hold its delay.
+fn changed_area(previous: &[u8], pixels: &[u8], width: usize) -> (usize, usize, usize, usize) {
+    let mut area: Option<(usize, usize, usize, usize)> = None;
+    for (i, _) in pixels.iter().zip(previous).enumerate().filter(|(_, (a, b))| a != b) {
+        let (x, y) = (i % width, i / width);
+        area = Some(match area {
+            None => (x, y, x + 1, y + 1),
+            Some((left, top, right, bottom)) => {
+                (left.min(x), top.min(y), right.max(x + 1), bottom.max(y + 1))
+            }
+        });
+    }
+    area.unwrap_or((0, 0, 1, 1))
+}
+
+/// Compress the color indices of a frame with the variable-length LZW codes that GIF uses.
+fn compress(indices: impl Iterator<Item = u8>) -> Vec<u8> {
+    let clear_code: u16 = 1 << COLOR_BITS;
+    let end_code = clear_code + 1;
+
+    let mut writer = BitWriter::default();
+    let mut table: HashMap<(u16, u8), u16> = HashMap::new();
+    let mut code_size = COLOR_BITS + 1;
+    let mut next_code = end_code + 1;
+    let mut prefix: Option<u16> = None;
+
+    writer.write(clear_code, code_size);
+    for index in indices {
+        let Some(code) = prefix else {
+            prefix = Some(u16::from(index));
+            continue;
+        };
+        if let Some(&longer) = table.get(&(code, index)) {
+            prefix = Some(longer);
+            continue;
+        }
+
+        writer.write(code, code_size);
+        // the decoder adds a code for every code that it reads, and reads longer codes as soon as
+        // its table needs them
+        if next_code >= 1 << code_size && code_size < 12 {
+            code_size += 1;
+        }
+        if next_code <= MAX_CODE {
+            table.insert((code, index), next_code);
+            next_code += 1;
+        } else {
+            writer.write(clear_code, code_size);
+            table.clear();
+            code_size = COLOR_BITS + 1;
+            next_code = end_code + 1;
+        }
+        prefix = Some(u16::from(index));
+    }
+
+    if let Some(code) = prefix {
+        writer.write(code, code_size);
+        if next_code >= 1 << code_size && code_size < 12 {
+            code_size += 1;
+        }
+    }
+    writer.write(end_code, code_size);
+    writer.finish()
+}
+
+/// Writes codes of any number of bits, starting with the least significant bit.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    bits: u8,
+}
+
+impl BitWriter {
+    fn write(&mut self, code: u16, size: u8) {
+        self.buffer |= u32::from(code) << self.bits;
+        self.bits += size;
+        while self.bits >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.bits -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.bits > 0 {
+            self.bytes.push(self.buffer as u8);
+        }
+        self.bytes
+    }
+}
@@ -17,26 +17,64 @@
 //
 // SPDX-License-Identifier: AGPL-3.0-or-later
 
-mod bot;
-mod common;
-mod game;
-mod layout;
+mod audio;
+mod bindings;
+mod gamepad;
+mod gif;
+mod netplay;
 mod render;
+mod settings;
+mod stats;
+mod storage;
+mod tournament;
 
-use game::GameState;
+use cordon_core::{bot, clock, common, game, layout, lockstep};
+
+use bindings::KeyBindings;
+use bot::BotDifficulty;
+use common::{Color, Palette, TrailPattern};
+use game::{ArenaLayout, ArenaMode, ArenaShape, ArenaSize, GameSpeed, GameState, MovementMode, TieRule};
+use render::{ThemePreset, TrailStyle};
+use settings::Settings;
+use tournament::Tournament;
 use leptos::{
-    ev::{fullscreenchange, keydown},
+    ev::{
+        fullscreenchange, gamepadconnected, gamepaddisconnected, keydown, keyup, pointerdown,
+        resize, touchend, touchmove, touchstart,
+    },
     html::Canvas,
     logging::log,
     prelude::*,
 };
 use leptos_use::{
-    use_active_element, use_document, use_event_listener, use_interval_fn, use_window,
+    use_document, use_event_listener, use_event_listener_with_options, use_interval_fn, use_raf_fn,
+    use_window, UseEventListenerOptions,
 };
+use serde::{Deserialize, Serialize};
+use std::{cell::Cell, collections::VecDeque, time::Duration};
 use web_sys::{
-    wasm_bindgen::JsCast, CanvasRenderingContext2d, HtmlElement, KeyboardEvent, NodeList,
+    js_sys, wasm_bindgen::JsCast, Blob, BlobPropertyBag, CanvasRenderingContext2d,
+    HtmlAnchorElement, HtmlCanvasElement, HtmlElement, KeyboardEvent, Url,
 };
 
+/// The items of the menu that is shown, in order, which the keyboard moves the focus between.
+/// Buttons elsewhere on the page, like the touch controls, are left out.
+fn menu_items() -> Vec<HtmlElement> {
+    let Ok(Some(menu)) = document().query_selector(".menu") else {
+        return vec![];
+    };
+    let Ok(items) = menu.query_selector_all("button:not(:disabled), input:not(:disabled), a[href]")
+    else {
+        return vec![];
+    };
+
+    items
+        .values()
+        .into_iter()
+        .filter_map(|item| item.ok()?.dyn_into().ok())
+        .collect()
+}
+
 fn toggle_fullscreen() {
     let document = use_document();
 
@@ -52,18 +90,599 @@ fn toggle_fullscreen() {
 }
 
 fn handle_action(e: &KeyboardEvent, player: &mut game::Player, direction: common::Direction) {
-    player.set_direction(direction);
+    if player.set_direction(direction) {
+        audio::play(audio::Sound::Turn);
+    }
     e.stop_propagation();
     e.prevent_default();
 }
 
+/// The shortest distance in pixels that a finger has to move over the screen to count as a swipe.
+const MIN_SWIPE_DISTANCE: f64 = 30.0;
+
+/// Determine the direction of a swipe that moved the specified distance horizontally and
+/// vertically, or `None` if the swipe was too short.
+fn swipe_direction(dx: f64, dy: f64) -> Option<common::Direction> {
+    if dx.abs().max(dy.abs()) < MIN_SWIPE_DISTANCE {
+        None
+    } else if dx.abs() > dy.abs() {
+        Some(if dx < 0.0 { common::Direction::West } else { common::Direction::East })
+    } else {
+        Some(if dy < 0.0 { common::Direction::North } else { common::Direction::South })
+    }
+}
+
+/// Steer the player that is controlled by touch input, which is the first player on this device
+/// that isn't a bot.
+fn steer_touch_player(game_state: &mut GameState, direction: common::Direction) {
+    let touch_player = game_state
+        .players
+        .iter_mut()
+        .find(|p| !matches!(p.controller, game::Controller::Bot(_) | game::Controller::Remote));
+
+    if let Some(player) = touch_player {
+        if player.set_direction(direction) {
+            audio::play(audio::Sound::Turn);
+        }
+    }
+}
+
 fn start_game(
+    num_humans: usize,
     num_players: usize,
+    options: GameOptions,
+    set_menu_page: WriteSignal<Option<MenuPage>>,
+    set_game_state: WriteSignal<game::GameState>,
+    set_initial_state: WriteSignal<Option<game::GameState>>,
+) {
+    let game_state = new_game(num_humans, num_players, &options);
+    play(game_state, set_menu_page, set_game_state, set_initial_state);
+}
+
+/// Create a game with the chosen options, which starts when it is passed to `play`.
+fn new_game(num_humans: usize, num_players: usize, options: &GameOptions) -> GameState {
+    let (width, height) = options.arena_size.dimensions(options.arena_shape);
+    let mut game_state = GameState::new(
+        num_humans,
+        num_players,
+        options.bot_difficulty,
+        options.max_score,
+        width,
+        height,
+        &options.player_colors,
+    );
+    game_state.set_arena(options.arena_mode, options.arena_layout);
+    game_state.set_start_length(options.start_length);
+    game_state.palette = options.palette;
+    game_state.tick_interval_ms = options.game_speed.tick_interval_ms();
+    game_state.speed_up = options.speed_up;
+    game_state.tie_rule = options.tie_rule;
+    game_state.movement_mode = options.movement_mode;
+    game_state.max_trail_length = options.max_trail_length;
+    game_state.set_shrink_interval(options.shrink_interval);
+    game_state.max_round_ticks = options.time_limit.then_some(game::ROUND_TIME_LIMIT_TICKS);
+    game_state.pickups_enabled = options.pickups;
+    if let Some(seed) = options.seed {
+        game_state.set_seed(seed);
+    }
+    for (player, pattern) in game_state.players.iter_mut().zip(options.player_patterns) {
+        player.pattern = pattern;
+    }
+    game_state
+}
+
+/// Close the menu and start playing the specified game.
+fn play(
+    mut game_state: GameState,
     set_menu_page: WriteSignal<Option<MenuPage>>,
     set_game_state: WriteSignal<game::GameState>,
+    set_initial_state: WriteSignal<Option<game::GameState>>,
 ) {
+    for index in gamepad::connected() {
+        game_state.connect_gamepad(index);
+    }
+
     set_menu_page.set(None);
-    set_game_state.set(GameState::new(num_players, 3));
+    set_initial_state.set(Some(game_state.clone()));
+    set_game_state.set(game_state);
+}
+
+/// Start a survival game, in which one human faces a single bot in the first round, and another
+/// bot every round after that.
+fn start_survival(
+    options: GameOptions,
+    set_menu_page: WriteSignal<Option<MenuPage>>,
+    set_game_state: WriteSignal<game::GameState>,
+    set_initial_state: WriteSignal<Option<game::GameState>>,
+) {
+    let mut game_state = new_game(1, 2, &options);
+    game_state.survival = true;
+    play(game_state, set_menu_page, set_game_state, set_initial_state);
+}
+
+/// Start a practice game, in which a single human drives around on their own. The trail never gets
+/// shorter, and a crash puts the player back at the start.
+fn start_practice(
+    options: GameOptions,
+    set_menu_page: WriteSignal<Option<MenuPage>>,
+    set_game_state: WriteSignal<game::GameState>,
+    set_initial_state: WriteSignal<Option<game::GameState>>,
+) {
+    let mut game_state = new_game(1, 1, &options);
+    game_state.practice = true;
+    game_state.max_trail_length = None;
+    play(game_state, set_menu_page, set_game_state, set_initial_state);
+}
+
+/// The number of human players in a tournament. The other places go to bots.
+const TOURNAMENT_HUMANS: usize = 2;
+
+/// Start a tournament between two human players and bots for the other places.
+fn start_tournament(
+    num_players: usize,
+    options: GameOptions,
+    set_tournament: WriteSignal<Option<Tournament>>,
+    set_menu_page: WriteSignal<Option<MenuPage>>,
+    set_game_state: WriteSignal<game::GameState>,
+    set_initial_state: WriteSignal<Option<game::GameState>>,
+) {
+    let entrants = (0..num_players)
+        .map(|i| match game::HUMAN_CONTROLLERS.get(i) {
+            Some(controller) if i < TOURNAMENT_HUMANS => controller.clone(),
+            _ => game::Controller::Bot(options.bot_difficulty),
+        })
+        .collect();
+    let tournament = Tournament::new(entrants);
+
+    start_match(&tournament, options, set_menu_page, set_game_state, set_initial_state);
+    set_tournament.set(Some(tournament));
+}
+
+/// Start the current match of the tournament, in which every player keeps the controller and the
+/// color that they have in the tournament.
+fn start_match(
+    tournament: &Tournament,
+    options: GameOptions,
+    set_menu_page: WriteSignal<Option<MenuPage>>,
+    set_game_state: WriteSignal<game::GameState>,
+    set_initial_state: WriteSignal<Option<game::GameState>>,
+) {
+    let Some((first, second)) = tournament.current_pair() else {
+        return;
+    };
+
+    let mut game_state = new_game(0, 2, &options);
+    for (player, entrant) in game_state.players.iter_mut().zip([first, second]) {
+        player.controller = tournament.entrants[entrant].clone();
+        player.color = options.player_colors[entrant];
+        player.pattern = options.player_patterns[entrant];
+    }
+
+    play(game_state, set_menu_page, set_game_state, set_initial_state);
+}
+
+/// A game between two bots, to play behind the main menu.
+fn demo_game(options: &GameOptions) -> GameState {
+    let (width, height) = options.arena_size.dimensions(options.arena_shape);
+    let mut game_state = GameState::new(
+        0,
+        2,
+        BotDifficulty::Medium,
+        options.max_score,
+        width,
+        height,
+        &options.player_colors,
+    );
+    game_state.palette = options.palette;
+    game_state.set_arena(options.arena_mode, options.arena_layout);
+    // bots can keep out of each other's way for a long time, so their rounds are on the clock
+    game_state.max_round_ticks = Some(game::ROUND_TIME_LIMIT_TICKS);
+    game_state
+}
+
+/// The local storage key under which a saved game is kept.
+const SAVED_GAME_KEY: &str = "cordon.saved_game";
+
+fn save_game(game_state: &GameState) {
+    storage::set(SAVED_GAME_KEY, &game_state.to_json());
+}
+
+fn load_game() -> Option<GameState> {
+    storage::get(SAVED_GAME_KEY).and_then(|json| GameState::from_json(&json).ok())
+}
+
+/// The size of a cell in a saved image of the board, in pixels.
+const IMAGE_CELL_SIZE: u32 = 16;
+
+/// The size of a cell in a saved animation of a game, in pixels. This is smaller than in an
+/// image, because every frame adds to the size of the file.
+const ANIMATION_CELL_SIZE: u32 = 8;
+
+/// The number of ticks that are encoded at once while saving an animation, before the progress is
+/// shown and the page can respond again.
+const ANIMATION_TICKS_PER_STEP: usize = 20;
+
+/// How long the last frame of an animation is shown before it starts over, in milliseconds.
+const ANIMATION_END_MS: u32 = 3000;
+
+/// A canvas of its own to draw the board of the specified game on, which isn't shown on the page.
+/// It has the same size on every screen.
+fn board_canvas(
+    game_state: &GameState,
+    cell_size: u32,
+) -> Result<(HtmlCanvasElement, CanvasRenderingContext2d), web_sys::wasm_bindgen::JsValue> {
+    let canvas = document().create_element("canvas")?.dyn_into::<HtmlCanvasElement>()?;
+    canvas.set_width(game_state.grid_width as u32 * cell_size);
+    canvas.set_height(game_state.grid_height as u32 * cell_size);
+
+    let c = canvas
+        .get_context("2d")?
+        .ok_or("canvas should have a 2d context")?
+        .dyn_into::<CanvasRenderingContext2d>()?;
+    Ok((canvas, c))
+}
+
+/// Let the browser download the file at the specified URL, under the specified name.
+fn download(url: &str, file_name: &str) -> Result<(), web_sys::wasm_bindgen::JsValue> {
+    let link = document().create_element("a")?.dyn_into::<HtmlAnchorElement>()?;
+    link.set_href(url);
+    link.set_download(file_name);
+    link.click();
+    Ok(())
+}
+
+/// Save a PNG image of the board as it is in the specified game, like the last frame of a game
+/// that is over. The board is drawn again on a canvas of its own, so the whole board is drawn
+/// before the image is made.
+fn save_board_image(
+    game_state: &GameState,
+    options: &GameOptions,
+) -> Result<(), web_sys::wasm_bindgen::JsValue> {
+    let (canvas, c) = board_canvas(game_state, IMAGE_CELL_SIZE)?;
+    let grid = layout::Grid::new(game_state.grid_width, game_state.grid_height, game_state);
+    render::draw_board(&c, &grid, &canvas, options.trail_style, &options.theme());
+    if options.crt_effect {
+        render::draw_crt(&c, &canvas);
+    }
+
+    // drawing is done before this returns, so the image has every trail and crash in it
+    let url = canvas.to_data_url_with_type("image/png")?;
+    download(&url, &format!("cordon-{}.png", game_state.seed))
+}
+
+/// An animated image of a recorded game that is being saved, one tick of the replay at a time.
+struct Animation {
+    game_state: GameState,
+    grid: layout::Grid,
+    canvas: HtmlCanvasElement,
+    c: CanvasRenderingContext2d,
+    encoder: gif::GifEncoder,
+    options: GameOptions,
+    num_moves: usize,
+}
+
+impl Animation {
+    /// Add a frame with the board as it is in the current tick of the replay.
+    fn add_frame(&mut self) -> Result<(), web_sys::wasm_bindgen::JsValue> {
+        let options = &self.options;
+        self.grid.reset(&self.game_state);
+        render::draw_board(&self.c, &self.grid, &self.canvas, options.trail_style, &options.theme());
+
+        let (width, height) = (self.canvas.width() as f64, self.canvas.height() as f64);
+        let pixels = self.c.get_image_data(0.0, 0.0, width, height)?.data();
+        let delay = self.game_state.tick_interval().unwrap_or(ANIMATION_END_MS);
+        self.encoder.add_frame(&pixels, delay);
+        Ok(())
+    }
+
+    /// The share of the recorded moves that is in the animation, from 0.0 to 1.0.
+    fn progress(&self) -> f64 {
+        let remaining = self.game_state.replay.as_ref().map_or(0, VecDeque::len);
+        1.0 - remaining as f64 / self.num_moves.max(1) as f64
+    }
+}
+
+/// Save an animated GIF image of the recorded game that starts with the specified state. Encoding
+/// all the frames takes a while, so it is done a few ticks at a time, with the share of the
+/// recording that is done in `set_progress`, until it is `None` again at the end.
+fn save_animation(
+    initial: GameState,
+    recording: &game::Recording,
+    options: GameOptions,
+    set_progress: WriteSignal<Option<f64>>,
+) -> Result<(), web_sys::wasm_bindgen::JsValue> {
+    let game_state = recording.playback(initial);
+    let (canvas, c) = board_canvas(&game_state, ANIMATION_CELL_SIZE)?;
+    let encoder = gif::GifEncoder::new(canvas.width() as u16, canvas.height() as u16);
+    let grid = layout::Grid::new(game_state.grid_width, game_state.grid_height, &game_state);
+
+    set_progress.set(Some(0.0));
+    let animation = Animation {
+        game_state,
+        grid,
+        canvas,
+        c,
+        encoder,
+        options,
+        num_moves: recording.moves.len(),
+    };
+    encode_animation(animation, set_progress);
+    Ok(())
+}
+
+/// Encode the next few ticks of the animation, and continue after the page had a chance to
+/// respond. At the end of the game, the image is downloaded.
+fn encode_animation(mut animation: Animation, set_progress: WriteSignal<Option<f64>>) {
+    for _ in 0..ANIMATION_TICKS_PER_STEP {
+        if let Err(e) = animation.add_frame() {
+            log!("Failed to save the animation: {:?}", e);
+            set_progress.try_set(None);
+            return;
+        }
+
+        if animation.game_state.phase == game::Phase::GameOver {
+            let seed = animation.game_state.seed;
+            if let Err(e) = save_gif(animation.encoder.finish(), seed) {
+                log!("Failed to save the animation: {:?}", e);
+            }
+            set_progress.try_set(None);
+            return;
+        }
+        animation.game_state.tick();
+    }
+
+    // the results screen may be gone by now, but the animation is still saved
+    set_progress.try_set(Some(animation.progress()));
+    set_timeout(move || encode_animation(animation, set_progress), Duration::ZERO);
+}
+
+/// Let the browser download the bytes of a GIF image.
+fn save_gif(bytes: Vec<u8>, seed: u64) -> Result<(), web_sys::wasm_bindgen::JsValue> {
+    let parts = js_sys::Array::of1(&js_sys::Uint8Array::from(bytes.as_slice()));
+    let properties = BlobPropertyBag::new();
+    properties.set_type("image/gif");
+    let blob = Blob::new_with_u8_array_sequence_and_options(&parts, &properties)?;
+
+    let url = Url::create_object_url_with_blob(&blob)?;
+    let downloaded = download(&url, &format!("cordon-{}.gif", seed));
+    // the download has to start before the URL stops working
+    set_timeout(
+        move || {
+            let _ = Url::revoke_object_url(&url);
+        },
+        Duration::from_secs(10),
+    );
+    downloaded
+}
+
+/// The location of the background music, relative to the page.
+const MUSIC_URL: &str = "assets/music.wav";
+
+/// How loud the music plays while a menu is open, relative to the chosen volume.
+const MENU_MUSIC_VOLUME: f64 = 0.3;
+
+/// A key binding that waits for the player to press the new key.
+#[derive(Clone, Debug, PartialEq)]
+struct PendingBinding {
+    controller: game::Controller,
+    direction: common::Direction,
+    /// Whether the last key that was pressed can't be used, because it already does something else.
+    key_in_use: bool,
+}
+
+/// The directions that can be bound to a key, with their names.
+const BINDABLE_DIRECTIONS: [(common::Direction, &str); 4] = [
+    (common::Direction::North, "Up"),
+    (common::Direction::West, "Left"),
+    (common::Direction::South, "Down"),
+    (common::Direction::East, "Right"),
+];
+
+/// The number of frames over which the debug view averages the render time.
+const RENDER_TIME_FRAMES: usize = 30;
+
+/// Measurements of the game and its rendering, for the debug view.
+#[derive(Clone, Debug, Default)]
+struct DebugMetrics {
+    /// How long the last frames took to draw, in milliseconds.
+    render_times: VecDeque<f64>,
+    /// When the last step happened, in milliseconds since the page was loaded.
+    last_tick: Option<f64>,
+    /// The time between the last two steps, in milliseconds.
+    tick_interval: Option<f64>,
+    segments: usize,
+    occupied_cells: usize,
+    /// The grid as text, one character per cell.
+    grid: String,
+}
+
+impl DebugMetrics {
+    fn record_render_time(&mut self, render_time: f64) {
+        self.render_times.push_back(render_time);
+        if self.render_times.len() > RENDER_TIME_FRAMES {
+            self.render_times.pop_front();
+        }
+    }
+
+    fn average_render_time(&self) -> f64 {
+        self.render_times.iter().sum::<f64>() / self.render_times.len().max(1) as f64
+    }
+
+    fn record_tick(&mut self, now: f64) {
+        self.tick_interval = self.last_tick.map(|last_tick| now - last_tick);
+        self.last_tick = Some(now);
+    }
+}
+
+/// The current time in milliseconds since the page was loaded, with sub-millisecond precision.
+fn now() -> f64 {
+    web_sys::window()
+        .and_then(|window| window.performance())
+        .map_or(0.0, |performance| performance.now())
+}
+
+/// Give the canvas as many pixels as it covers on the screen, so that the board is sharp on
+/// high-DPI screens, where a CSS pixel is more than one pixel of the screen. The size on the screen
+/// is set by the stylesheet, so it doesn't change. The board is laid out from the pixels of the
+/// canvas, so every cell is drawn square and a whole number of screen pixels wide.
+fn fit_canvas(canvas: &HtmlCanvasElement) {
+    let scale = web_sys::window().map_or(1.0, |window| window.device_pixel_ratio());
+    let rect = canvas.get_bounding_client_rect();
+    canvas.set_width((rect.width() * scale).round() as u32);
+    canvas.set_height((rect.height() * scale).round() as u32);
+}
+
+/// The time between two runs of the game clock in milliseconds. The time between two ticks of
+/// every phase is a multiple of this, or close to one.
+const CLOCK_INTERVAL_MS: u32 = 10;
+
+thread_local! {
+    /// Whether a game clock is running. There should only ever be one, or the game speeds up.
+    static CLOCK_RUNNING: Cell<bool> = const { Cell::new(false) };
+}
+
+/// The maximum number of players in a game.
+const MAX_PLAYERS: usize = 4;
+
+/// The choices in the menus, which are used to set up the next game.
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+struct GameOptions {
+    bot_difficulty: BotDifficulty,
+    arena_size: ArenaSize,
+    arena_shape: ArenaShape,
+    arena_mode: ArenaMode,
+    arena_layout: ArenaLayout,
+    game_speed: GameSpeed,
+    speed_up: bool,
+    tie_rule: TieRule,
+    movement_mode: MovementMode,
+    max_trail_length: Option<usize>,
+    /// The number of steps between two rings that close in on the arena, or `None` to keep it the
+    /// same size.
+    shrink_interval: Option<u32>,
+    /// End a round that goes on for too long, and give the point to the player with the most room.
+    time_limit: bool,
+    /// The number of segments that every trail starts with, including the head.
+    start_length: usize,
+    /// The score that wins the game.
+    max_score: u32,
+    pickups: bool,
+    /// The seed of the next match, or `None` for a random one. Unlike the other choices, it is
+    /// not remembered between visits.
+    #[serde(skip)]
+    seed: Option<u64>,
+    player_colors: [Color; MAX_PLAYERS],
+    /// The colors that players choose from, and that obstacles and crashes are drawn in.
+    palette: Palette,
+    /// The line through the trail of every player, so that players differ by more than color.
+    player_patterns: [TrailPattern; MAX_PLAYERS],
+    trail_style: TrailStyle,
+    theme: ThemePreset,
+    /// Draw scanlines and a vignette over the arena, like an old CRT screen.
+    crt_effect: bool,
+}
+
+impl Default for GameOptions {
+    fn default() -> Self {
+        GameOptions {
+            bot_difficulty: BotDifficulty::Easy,
+            arena_size: ArenaSize::Medium,
+            arena_shape: ArenaShape::Classic,
+            arena_mode: ArenaMode::Walled,
+            arena_layout: ArenaLayout::Border,
+            game_speed: GameSpeed::Normal,
+            speed_up: false,
+            tie_rule: TieRule::NoPoints,
+            movement_mode: MovementMode::RoundRobin,
+            max_trail_length: None,
+            shrink_interval: None,
+            time_limit: false,
+            start_length: 1,
+            max_score: 3,
+            pickups: false,
+            seed: None,
+            player_colors: [Color::red(), Color::blue(), Color::green(), Color::white()],
+            palette: Palette::Standard,
+            player_patterns: [TrailPattern::Solid; MAX_PLAYERS],
+            trail_style: TrailStyle::default(),
+            theme: ThemePreset::default(),
+            crt_effect: false,
+        }
+    }
+}
+
+impl GameOptions {
+    /// The colors of the chosen theme, with the collision color of the chosen palette.
+    fn theme(&self) -> render::Theme {
+        self.theme.theme(self.palette)
+    }
+}
+
+/// The choices for the maximum trail length, where `None` means that trails never get shorter.
+const TRAIL_LENGTHS: [Option<usize>; 4] = [None, Some(25), Some(50), Some(100)];
+
+/// Pick the next choice for the maximum trail length, wrapping around to unlimited.
+fn next_trail_length(max_trail_length: Option<usize>) -> Option<usize> {
+    let current = TRAIL_LENGTHS.iter().position(|l| *l == max_trail_length).unwrap_or(0);
+    TRAIL_LENGTHS[(current + 1) % TRAIL_LENGTHS.len()]
+}
+
+/// The choices for how quickly the arena closes in, where `None` means that it doesn't.
+const SHRINK_INTERVALS: [Option<u32>; 3] = [None, Some(100), Some(50)];
+
+/// Pick the next choice for how quickly the arena closes in, wrapping around to not at all.
+fn next_shrink_interval(shrink_interval: Option<u32>) -> Option<u32> {
+    let current = SHRINK_INTERVALS.iter().position(|i| *i == shrink_interval).unwrap_or(0);
+    SHRINK_INTERVALS[(current + 1) % SHRINK_INTERVALS.len()]
+}
+
+/// The choices for the number of segments that the trails start with.
+const START_LENGTHS: [usize; 3] = [1, 3, 5];
+
+/// Pick the next choice for the starting trail length, wrapping around to just the head.
+fn next_start_length(start_length: usize) -> usize {
+    let current = START_LENGTHS.iter().position(|l| *l == start_length).unwrap_or(0);
+    START_LENGTHS[(current + 1) % START_LENGTHS.len()]
+}
+
+/// The choices for the score that wins the game: with two players, best of 3, 5 or 7 rounds.
+const MAX_SCORES: [u32; 3] = [2, 3, 4];
+
+/// Pick the next choice for the score that wins the game, wrapping around to the lowest.
+fn next_max_score(max_score: u32) -> u32 {
+    let current = MAX_SCORES.iter().position(|s| *s == max_score).unwrap_or(0);
+    MAX_SCORES[(current + 1) % MAX_SCORES.len()]
+}
+
+/// The label of the choice for the score that wins the game. The winning score depends on the
+/// number of players, so the label shows it for every number of players.
+fn max_score_label(max_score: u32) -> String {
+    let more_players = game::MIN_PLAYERS + 1..=MAX_PLAYERS;
+    let scores: Vec<String> = more_players
+        .clone()
+        .map(|num_players| game::winning_score(max_score, num_players).to_string())
+        .collect();
+    let counts: Vec<String> = more_players.map(|num_players| num_players.to_string()).collect();
+
+    format!(
+        "Rounds: First to {} Points ({} with {} Players)",
+        game::winning_score(max_score, game::MIN_PLAYERS),
+        scores.join(" / "),
+        counts.join(" / "),
+    )
+}
+
+/// Pick the next color from the swatches of the palette, skipping colors that other players already
+/// use.
+fn next_player_color(palette: Palette, colors: &[Color], player: usize) -> Color {
+    let swatches = palette.swatches();
+    let current = swatches.iter().position(|c| *c == colors[player]).unwrap_or(0);
+
+    (1..swatches.len())
+        .map(|offset| swatches[(current + offset) % swatches.len()])
+        .find(|swatch| !colors.contains(swatch))
+        .unwrap_or(colors[player])
 }
 
 #[derive(Debug, Clone)]
@@ -71,15 +690,57 @@ enum MenuPage {
     Main,
     NewGame,
     Settings,
+    Statistics,
     About,
+    Results,
+    /// The standings of the tournament, between its matches and at the end.
+    Standings,
+    /// Hosting or joining a game against a player on another device.
+    Online,
+}
+
+/// An online game: the connection to the other client, and the ticks that were played in step
+/// with it once the game has started.
+struct OnlineGame {
+    connection: netplay::Connection,
+    lockstep: Option<lockstep::Lockstep>,
+}
+
+/// How far along an online game is.
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum OnlineStatus {
+    /// Connected, but the other player isn't there yet.
+    Waiting,
+    Playing,
+    /// The connection could not be made, or it was lost.
+    Disconnected,
 }
 
 #[component]
 fn Menu(
     menu_page: ReadSignal<Option<MenuPage>>,
     set_menu_page: WriteSignal<Option<MenuPage>>,
+    game_state: ReadSignal<game::GameState>,
     set_game_state: WriteSignal<game::GameState>,
+    initial_state: ReadSignal<Option<game::GameState>>,
+    set_initial_state: WriteSignal<Option<game::GameState>>,
+    last_game: ReadSignal<Option<game::GameState>>,
     is_fullscreen: ReadSignal<bool>,
+    muted: ReadSignal<bool>,
+    set_muted: WriteSignal<bool>,
+    music_volume: ReadSignal<f64>,
+    set_music_volume: WriteSignal<f64>,
+    key_bindings: ReadSignal<KeyBindings>,
+    pending_binding: ReadSignal<Option<PendingBinding>>,
+    set_pending_binding: WriteSignal<Option<PendingBinding>>,
+    options: ReadSignal<GameOptions>,
+    set_options: WriteSignal<GameOptions>,
+    has_saved_game: ReadSignal<bool>,
+    tournament: ReadSignal<Option<Tournament>>,
+    set_tournament: WriteSignal<Option<Tournament>>,
+    online: StoredValue<Option<OnlineGame>, LocalStorage>,
+    online_status: ReadSignal<Option<OnlineStatus>>,
+    set_online_status: WriteSignal<Option<OnlineStatus>>,
 ) -> impl IntoView {
     move || {
         match menu_page.get().expect("menu page should be set") {
@@ -88,12 +749,36 @@ fn Menu(
                 <div class="menu">
                     <h1>"Cordon"</h1>
                     <div class="items">
+                        <Show when=move || has_saved_game.get()>
+                            <button on:click={move |_| {
+                                if let Some(game_state) = load_game() {
+                                    set_menu_page.set(None);
+                                    set_game_state.set(game_state);
+                                }
+                            }}>
+                                "Continue"
+                            </button>
+                        </Show>
+                        <Show when=move || initial_state.with(Option::is_some) && last_game.with(Option::is_some)>
+                            <button on:click={move |_| {
+                                let initial = initial_state.get_untracked();
+                                if let (Some(initial), Some(last_game)) = (initial, last_game.get_untracked()) {
+                                    set_menu_page.set(None);
+                                    set_game_state.set(last_game.recording.playback(initial));
+                                }
+                            }}>
+                                "Replay Last Game"
+                            </button>
+                        </Show>
                         <button on:click={move |_| set_menu_page.set(Some(MenuPage::NewGame))}>
                             "New Game"
                         </button>
                         <button on:click={move |_| set_menu_page.set(Some(MenuPage::Settings))}>
                             "Settings"
                         </button>
+                        <button on:click={move |_| set_menu_page.set(Some(MenuPage::Statistics))}>
+                            "Statistics"
+                        </button>
                         <button on:click={move |_| set_menu_page.set(Some(MenuPage::About))}>
                             "About"
                         </button>
@@ -107,12 +792,102 @@ fn Menu(
                 <div class="menu">
                     <h1>"New Game"</h1>
                     <div class="items">
-                        <button on:click={move |_| start_game(1, set_menu_page, set_game_state)}>
+                        <button on:click={move |_| start_game(1, 2, options.get(), set_menu_page, set_game_state, set_initial_state)}>
                             "One Player"
                         </button>
-                        <button on:click={move |_| start_game(2, set_menu_page, set_game_state)}>
+                        <button on:click={move |_| start_game(2, 2, options.get(), set_menu_page, set_game_state, set_initial_state)}>
                             "Two Players"
                         </button>
+                        <button on:click={move |_| start_game(2, 3, options.get(), set_menu_page, set_game_state, set_initial_state)}>
+                            "Two Players + 1 Bot"
+                        </button>
+                        <button on:click={move |_| start_game(2, 4, options.get(), set_menu_page, set_game_state, set_initial_state)}>
+                            "Two Players + 2 Bots"
+                        </button>
+                        <button on:click={move |_| start_game(3, 3, options.get(), set_menu_page, set_game_state, set_initial_state)}>
+                            "Three Players"
+                        </button>
+                        <button on:click={move |_| start_game(4, 4, options.get(), set_menu_page, set_game_state, set_initial_state)}>
+                            "Four Players"
+                        </button>
+                        <button on:click={move |_| start_survival(options.get(), set_menu_page, set_game_state, set_initial_state)}>
+                            "Survival"
+                        </button>
+                        <button on:click={move |_| start_practice(options.get(), set_menu_page, set_game_state, set_initial_state)}>
+                            "Practice"
+                        </button>
+                        <button on:click={move |_| set_menu_page.set(Some(MenuPage::Online))}>
+                            "Online: Two Players"
+                        </button>
+                        <button on:click={move |_| start_tournament(3, options.get(), set_tournament, set_menu_page, set_game_state, set_initial_state)}>
+                            "Tournament: Two Players + 1 Bot"
+                        </button>
+                        <button on:click={move |_| start_tournament(4, options.get(), set_tournament, set_menu_page, set_game_state, set_initial_state)}>
+                            "Tournament: Two Players + 2 Bots"
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.max_score = next_max_score(o.max_score))}>
+                            {move || max_score_label(options.get().max_score)}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.bot_difficulty = o.bot_difficulty.next())}>
+                            {move || format!("Bot: {}", options.get().bot_difficulty)}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.arena_size = o.arena_size.next())}>
+                            {move || format!("Arena: {}", options.get().arena_size)}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.arena_shape = o.arena_shape.next())}>
+                            {move || format!("Shape: {}", options.get().arena_shape)}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.arena_mode = o.arena_mode.next())}>
+                            {move || format!("Edges: {}", options.get().arena_mode)}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.arena_layout = o.arena_layout.next())}>
+                            {move || format!("Layout: {}", options.get().arena_layout)}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.game_speed = o.game_speed.next())}>
+                            {move || format!("Speed: {}", options.get().game_speed)}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.speed_up = !o.speed_up)}>
+                            {move || if options.get().speed_up { "Speed Up: On" } else { "Speed Up: Off" }}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.tie_rule = o.tie_rule.next())}>
+                            {move || format!("Ties: {}", options.get().tie_rule)}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.movement_mode = o.movement_mode.next())}>
+                            {move || format!("Movement: {}", options.get().movement_mode)}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.max_trail_length = next_trail_length(o.max_trail_length))}>
+                            {move || match options.get().max_trail_length {
+                                Some(length) => format!("Trail Length: {}", length),
+                                None => "Trail Length: Unlimited".to_string(),
+                            }}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.shrink_interval = next_shrink_interval(o.shrink_interval))}>
+                            {move || match options.get().shrink_interval {
+                                Some(interval) => format!("Shrinking Arena: Every {} Steps", interval),
+                                None => "Shrinking Arena: Off".to_string(),
+                            }}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.time_limit = !o.time_limit)}>
+                            {move || if options.get().time_limit { "Time Limit: On" } else { "Time Limit: Off" }}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.start_length = next_start_length(o.start_length))}>
+                            {move || format!("Start Length: {}", options.get().start_length)}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.pickups = !o.pickups)}>
+                            {move || if options.get().pickups { "Pickups: On" } else { "Pickups: Off" }}
+                        </button>
+                        <label class="field">
+                            "Seed"
+                            <input
+                                type="text"
+                                inputmode="numeric"
+                                placeholder="Random"
+                                prop:value={move || options.get().seed.map(|s| s.to_string()).unwrap_or_default()}
+                                on:change={move |e| set_options.update(|o| {
+                                    o.seed = event_target_value(&e).trim().parse().ok();
+                                })}
+                            />
+                        </label>
                     </div>
                     <button on:click={move |_| set_menu_page.set(Some(MenuPage::Main))}>
                         "Back"
@@ -129,6 +904,72 @@ fn Menu(
                         <button on:click={move |_| toggle_fullscreen()}>
                             {move || if is_fullscreen.get() { "Exit Fullscreen" } else { "Fullscreen" }}
                         </button>
+                        <button on:click={move |_| set_options.update(|o| o.arena_mode = o.arena_mode.next())}>
+                            {move || format!("Edges: {}", options.get().arena_mode)}
+                        </button>
+                        <ArenaPreview options />
+                        <button on:click={move |_| set_options.update(|o| o.trail_style = o.trail_style.next())}>
+                            {move || format!("Trails: {}", options.get().trail_style)}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.theme = o.theme.next())}>
+                            {move || format!("Theme: {}", options.get().theme)}
+                        </button>
+                        <button on:click={move |_| set_options.update(|o| o.crt_effect = !o.crt_effect)}>
+                            {move || if options.get().crt_effect { "CRT Effect: On" } else { "CRT Effect: Off" }}
+                        </button>
+                        <button on:click={move |_| set_muted.update(|m| *m = !*m)}>
+                            {move || if muted.get() { "Sound: Off" } else { "Sound: On" }}
+                        </button>
+                        <label class="slider">
+                            {move || format!("Music: {}%", (music_volume.get() * 100.0).round())}
+                            <input
+                                type="range"
+                                min="0"
+                                max="100"
+                                prop:value={move || (music_volume.get() * 100.0).round().to_string()}
+                                on:input={move |e| {
+                                    if let Ok(value) = event_target_value(&e).parse::<f64>() {
+                                        set_music_volume.set(value / 100.0);
+                                    }
+                                }}
+                            />
+                        </label>
+                        {game::HUMAN_CONTROLLERS.iter().enumerate().flat_map(|(i, controller)| {
+                            BINDABLE_DIRECTIONS.map(|(direction, name)| view! {
+                                <KeyBindingButton
+                                    label={format!("Player {} {}", i + 1, name)}
+                                    controller={controller.clone()}
+                                    direction
+                                    key_bindings
+                                    pending_binding
+                                    set_pending_binding
+                                />
+                            })
+                        }).collect_view()}
+                        <button on:click={move |_| set_options.update(|o| {
+                            // the players start over with the first colors of the new palette
+                            o.palette = o.palette.next();
+                            let swatches = o.palette.swatches();
+                            o.player_colors = std::array::from_fn(|i| swatches[i]);
+                        })}>
+                            {move || format!("Palette: {}", options.get().palette)}
+                        </button>
+                        {(0..MAX_PLAYERS).map(|i| view! {
+                            <button on:click={move |_| set_options.update(|o| {
+                                o.player_colors[i] = next_player_color(o.palette, &o.player_colors, i);
+                            })}>
+                                {format!("Player {} Color ", i + 1)}
+                                <span
+                                    class="swatch"
+                                    style:background-color={move || options.get().player_colors[i].to_string()}
+                                ></span>
+                            </button>
+                            <button on:click={move |_| set_options.update(|o| {
+                                o.player_patterns[i] = o.player_patterns[i].next();
+                            })}>
+                                {move || format!("Player {} Pattern: {}", i + 1, options.get().player_patterns[i])}
+                            </button>
+                        }).collect_view()}
                     </div>
                     <button on:click={move |_| set_menu_page.set(Some(MenuPage::Main))}>
                         "Back"
@@ -137,6 +978,38 @@ fn Menu(
             </div>
         }
         .into_any(),
+        MenuPage::Statistics => {
+            let stats = stats::Stats::load();
+            view! {
+                <div class="center">
+                    <div class="menu">
+                        <h1>"Statistics"</h1>
+                        <div class="items">
+                            <p>{format!("Games Played: {}", stats.games_played)}</p>
+                            <p>{format!("Draws: {}", stats.draws)}</p>
+                            <p>{format!("Longest Trail: {}", stats.longest_trail)}</p>
+                            <p>"Wins by Controller:"</p>
+                            {stats.wins_by_controller.iter().map(|(controller, wins)| view! {
+                                <p>{format!("{}: {}", controller, wins)}</p>
+                            }).collect_view()}
+                            <p>"Wins by Color:"</p>
+                            {stats.wins_by_color.iter().map(|(color, wins)| view! {
+                                <p>
+                                    <span class="swatch" style:background-color={color.to_string()}></span>
+                                    {format!(" {}", wins)}
+                                </p>
+                            }).collect_view()}
+                            <p>"Survival High Scores:"</p>
+                            <HighScoreTable />
+                        </div>
+                        <button on:click={move |_| set_menu_page.set(Some(MenuPage::Main))}>
+                            "Back"
+                        </button>
+                    </div>
+                </div>
+            }
+        }
+        .into_any(),
         MenuPage::About => view! {
             <div class="center">
                 <div class="menu">
@@ -165,7 +1038,461 @@ fn Menu(
                 </div>
             </div>
         }.into_any(),
+        MenuPage::Results => view! {
+            <Results set_menu_page game_state set_game_state initial_state set_initial_state options />
+        }.into_any(),
+        MenuPage::Standings => view! {
+            <Standings tournament set_menu_page set_game_state set_initial_state options />
+        }.into_any(),
+        MenuPage::Online => view! {
+            <Online
+                set_menu_page
+                set_game_state
+                set_initial_state
+                options
+                online
+                online_status
+                set_online_status
+            />
+        }.into_any(),
+    }
+    }
+}
+
+/// Describe the outcome of a finished game, like "Player 2 Wins", "Draw" or, for a survival game,
+/// "Survived 3 Rounds".
+fn results_title(game_state: &GameState) -> String {
+    if game_state.survival {
+        let rounds = game_state.players.first().map_or(0, |p| p.score);
+        return format!("Survived {} Round{}", rounds, if rounds == 1 { "" } else { "s" });
+    }
+
+    match game_state.winner() {
+        Some(winner) => format!("Player {} Wins", winner + 1),
+        None => "Draw".to_string(),
+    }
+}
+
+/// The screen after a game is over, with the final scores and the winner.
+#[component]
+fn Results(
+    set_menu_page: WriteSignal<Option<MenuPage>>,
+    game_state: ReadSignal<game::GameState>,
+    set_game_state: WriteSignal<game::GameState>,
+    initial_state: ReadSignal<Option<game::GameState>>,
+    set_initial_state: WriteSignal<Option<game::GameState>>,
+    options: ReadSignal<GameOptions>,
+) -> impl IntoView {
+    let (animation_progress, set_animation_progress) = signal(None::<f64>);
+
+    // a rematch is played with the same seed, which gives the same board and the same bots
+    let restart = move |seed: Option<u64>| {
+        let options = GameOptions {
+            seed,
+            ..options.get_untracked()
+        };
+        if game_state.with_untracked(|s| s.survival) {
+            start_survival(options, set_menu_page, set_game_state, set_initial_state);
+            return;
+        }
+
+        let (num_humans, num_players) = game_state.with_untracked(|s| {
+            let num_bots = s
+                .players
+                .iter()
+                .filter(|p| matches!(p.controller, game::Controller::Bot(_)))
+                .count();
+            (s.players.len() - num_bots, s.players.len())
+        });
+        start_game(
+            num_humans,
+            num_players,
+            options,
+            set_menu_page,
+            set_game_state,
+            set_initial_state,
+        );
+    };
+
+    view! {
+        <div class="center">
+            <div class="menu">
+                <h1>{move || game_state.with(results_title)}</h1>
+                <div class="items">
+                    {move || if game_state.with(|s| s.survival) {
+                        view! { <HighScoreTable /> }.into_any()
+                    } else {
+                        game_state.with(|s| s.players.iter().enumerate().map(|(i, player)| view! {
+                            <p>
+                                <span class="swatch" style:background-color={player.color.to_string()}></span>
+                                {format!(" Player {}: {}", i + 1, player.score)}
+                            </p>
+                        }).collect_view()).into_any()
+                    }}
+                    <p>{move || format!("Seed: {}", game_state.with(|s| s.seed))}</p>
+                    <button on:click={move |_| restart(options.get_untracked().seed)}>
+                        "Play Again"
+                    </button>
+                    <button on:click={move |_| restart(Some(game_state.with_untracked(|s| s.seed)))}>
+                        "Rematch"
+                    </button>
+                    <button on:click={move |_| {
+                        let saved = game_state.with_untracked(|s| {
+                            save_board_image(s, &options.get_untracked())
+                        });
+                        if let Err(e) = saved {
+                            log!("Failed to save the image: {:?}", e);
+                        }
+                    }}>
+                        "Save Image"
+                    </button>
+                    <Show when=move || initial_state.with(Option::is_some)>
+                        <button
+                            disabled=move || animation_progress.get().is_some()
+                            on:click={move |_| {
+                                let Some(initial) = initial_state.get_untracked() else {
+                                    return;
+                                };
+                                let options = options.get_untracked();
+                                let saved = game_state.with_untracked(|s| {
+                                    save_animation(initial, &s.recording, options, set_animation_progress)
+                                });
+                                if let Err(e) = saved {
+                                    log!("Failed to save the animation: {:?}", e);
+                                }
+                            }}
+                        >
+                            {move || match animation_progress.get() {
+                                Some(progress) => format!("Saving Animation: {:.0}%", progress * 100.0),
+                                None => "Save Animation".to_string(),
+                            }}
+                        </button>
+                    </Show>
+                </div>
+                <button on:click={move |_| set_menu_page.set(Some(MenuPage::Main))}>
+                    "Main Menu"
+                </button>
+            </div>
+        </div>
+    }
+}
+
+/// The best survival games, with the number of rounds survived and the difficulty of the bots.
+#[component]
+fn HighScoreTable() -> impl IntoView {
+    let high_scores = stats::HighScores::load();
+
+    view! {
+        {high_scores.entries.is_empty().then_some(view! { <p>"None yet"</p> })}
+        {high_scores.entries.iter().enumerate().map(|(i, entry)| view! {
+            <p>{format!("{}. {} rounds ({})", i + 1, entry.rounds, entry.bot_difficulty)}</p>
+        }).collect_view()}
     }
+}
+
+/// A small picture of the arena that a new game starts in, so that the effect of the options can
+/// be seen right away.
+#[component]
+fn ArenaPreview(options: ReadSignal<GameOptions>) -> impl IntoView {
+    let canvas_ref = NodeRef::<Canvas>::new();
+
+    Effect::new(move || {
+        let options = options.get();
+        let Some(canvas) = canvas_ref.get() else {
+            return;
+        };
+
+        let (width, height) = options.arena_size.dimensions(options.arena_shape);
+        let mut game_state =
+            GameState::new(0, 2, BotDifficulty::Easy, 1, width, height, &options.player_colors);
+        game_state.palette = options.palette;
+        game_state.set_arena(options.arena_mode, options.arena_layout);
+        game_state.set_start_length(options.start_length);
+        // without the countdown, the preview shows the players where they start
+        game_state.set_phase(game::Phase::Step);
+
+        fit_canvas(&canvas);
+
+        let c = canvas
+            .get_context("2d")
+            .unwrap()
+            .unwrap()
+            .dyn_into::<CanvasRenderingContext2d>()
+            .unwrap();
+
+        let grid = layout::Grid::new(width, height, &game_state);
+        render::draw_board(&c, &grid, &canvas, options.trail_style, &options.theme());
+    });
+
+    view! {
+        <canvas class="preview" node_ref={canvas_ref}></canvas>
+    }
+}
+
+/// A button that shows the key that steers a player in one direction, and that waits for a new
+/// key when it is clicked.
+#[component]
+fn KeyBindingButton(
+    label: String,
+    controller: game::Controller,
+    direction: common::Direction,
+    key_bindings: ReadSignal<KeyBindings>,
+    pending_binding: ReadSignal<Option<PendingBinding>>,
+    set_pending_binding: WriteSignal<Option<PendingBinding>>,
+) -> impl IntoView {
+    let pending = PendingBinding {
+        controller: controller.clone(),
+        direction,
+        key_in_use: false,
+    };
+
+    view! {
+        <button on:click={move |_| set_pending_binding.set(Some(pending.clone()))}>
+            {move || {
+                let key = match pending_binding.get() {
+                    Some(p) if p.controller == controller && p.direction == direction => {
+                        if p.key_in_use { "Key in use, press another" } else { "Press a key" }
+                            .to_string()
+                    }
+                    _ => key_bindings.with(|b| {
+                        b.key(&controller, direction).map(bindings::key_name).unwrap_or_default()
+                    }),
+                };
+                format!("{}: {}", label, key)
+            }}
+        </button>
+    }
+}
+
+/// The screen to host or join an online game. Both players connect to the same address on a relay
+/// server, which passes the messages of one client on to the other.
+#[component]
+fn Online(
+    set_menu_page: WriteSignal<Option<MenuPage>>,
+    set_game_state: WriteSignal<game::GameState>,
+    set_initial_state: WriteSignal<Option<game::GameState>>,
+    options: ReadSignal<GameOptions>,
+    online: StoredValue<Option<OnlineGame>, LocalStorage>,
+    online_status: ReadSignal<Option<OnlineStatus>>,
+    set_online_status: WriteSignal<Option<OnlineStatus>>,
+) -> impl IntoView {
+    let (server_url, set_server_url) = signal(netplay::server_url());
+    let (rollback, set_rollback) = signal(netplay::is_rollback_enabled());
+
+    let start = move |mut game_state: GameState, role: lockstep::Role| {
+        // on each client, the keyboard steers its own player, and the other one comes over the network
+        game_state.players[role.local_player()].controller = game::Controller::Wasd;
+        game_state.players[role.remote_player()].controller = game::Controller::Remote;
+
+        online.update_value(|o| {
+            if let Some(o) = o {
+                o.lockstep = Some(lockstep::Lockstep::new(role, rollback.get_untracked()));
+            }
+        });
+        set_online_status.set(Some(OnlineStatus::Playing));
+        play(game_state, set_menu_page, set_game_state, set_initial_state);
+    };
+
+    let connect = move |role: lockstep::Role| {
+        let url = server_url.get_untracked();
+        netplay::set_server_url(&url);
+
+        let is_waiting = move || online.with_value(|o| o.as_ref().is_some_and(|o| o.lockstep.is_none()));
+        let send = move |message| {
+            online.with_value(|o| {
+                if let Some(o) = o {
+                    o.connection.send(&message);
+                }
+            });
+        };
+
+        let on_message = move |message| match message {
+            netplay::Message::Join if role == lockstep::Role::Guest && is_waiting() => {
+                send(netplay::Message::Join);
+            }
+            netplay::Message::Join if role == lockstep::Role::Host && is_waiting() => {
+                let game_state = new_game(2, 2, &options.get_untracked());
+                send(netplay::Message::Start(game_state.to_json()));
+                start(game_state, role);
+            }
+            netplay::Message::Start(json) if role == lockstep::Role::Guest => {
+                match GameState::from_json(&json) {
+                    Ok(game_state) => start(game_state, role),
+                    Err(e) => log!("Could not start the online game: {}", e),
+                }
+            }
+            netplay::Message::Input(input) => online.update_value(|o| {
+                if let Some(lockstep) = o.as_mut().and_then(|o| o.lockstep.as_mut()) {
+                    lockstep.receive(input);
+                }
+            }),
+            _ => {}
+        };
+        let on_close = move || set_online_status.set(Some(OnlineStatus::Disconnected));
+
+        match netplay::Connection::open(&url, on_message, on_close) {
+            Some(connection) => {
+                online.set_value(Some(OnlineGame { connection, lockstep: None }));
+                set_online_status.set(Some(OnlineStatus::Waiting));
+            }
+            None => set_online_status.set(Some(OnlineStatus::Disconnected)),
+        }
+    };
+
+    let back = move |_| {
+        online.set_value(None);
+        set_online_status.set(None);
+        set_menu_page.set(Some(MenuPage::NewGame));
+    };
+
+    view! {
+        <div class="center">
+            <div class="menu">
+                <h1>"Online"</h1>
+                <div class="items">
+                    <label class="field">
+                        "Server"
+                        <input
+                            type="text"
+                            placeholder="wss://example.com/room"
+                            prop:value=server_url
+                            on:change={move |e| set_server_url.set(event_target_value(&e).trim().to_string())}
+                        />
+                    </label>
+                    <button on:click={move |_| {
+                        set_rollback.update(|r| *r = !*r);
+                        netplay::set_rollback_enabled(rollback.get_untracked());
+                    }}>
+                        {move || if rollback.get() { "Rollback: On" } else { "Rollback: Off" }}
+                    </button>
+                    <button on:click={move |_| connect(lockstep::Role::Host)}>"Host Game"</button>
+                    <button on:click={move |_| connect(lockstep::Role::Guest)}>"Join Game"</button>
+                    <p>
+                        {move || match online_status.get() {
+                            None => "Both players enter the same server",
+                            Some(OnlineStatus::Waiting) => "Waiting for the other player...",
+                            Some(OnlineStatus::Playing) => "Connected",
+                            Some(OnlineStatus::Disconnected) => "Could not connect to the server",
+                        }}
+                    </p>
+                </div>
+                <button on:click=back>"Back"</button>
+            </div>
+        </div>
+    }
+}
+
+/// The screen between the matches of a tournament, with the matches won and the points of every
+/// player, from first to last place.
+#[component]
+fn Standings(
+    tournament: ReadSignal<Option<Tournament>>,
+    set_menu_page: WriteSignal<Option<MenuPage>>,
+    set_game_state: WriteSignal<game::GameState>,
+    set_initial_state: WriteSignal<Option<game::GameState>>,
+    options: ReadSignal<GameOptions>,
+) -> impl IntoView {
+    let next_match = move |_| {
+        tournament.with_untracked(|t| {
+            if let Some(t) = t {
+                start_match(t, options.get_untracked(), set_menu_page, set_game_state, set_initial_state);
+            }
+        });
+    };
+
+    view! {
+        <div class="center">
+            <div class="menu">
+                {move || tournament.with(|t| t.as_ref().map(|t| {
+                    let standings = t.standings();
+                    let title = if t.is_finished() {
+                        format!("Player {} Wins the Tournament", standings[0] + 1)
+                    } else {
+                        format!("Match {} of {}", t.current_match + 1, t.schedule.len())
+                    };
+                    let next_pair = t.current_pair().map(|(first, second)| {
+                        format!("Next: Player {} vs Player {}", first + 1, second + 1)
+                    });
+                    let colors = options.get_untracked().player_colors;
+
+                    view! {
+                        <h1>{title}</h1>
+                        <div class="items">
+                            {standings.into_iter().map(|i| view! {
+                                <p>
+                                    <span class="swatch" style:background-color={colors[i].to_string()}></span>
+                                    {format!(" Player {}: {} won, {} points", i + 1, t.wins[i], t.points[i])}
+                                </p>
+                            }).collect_view()}
+                            {next_pair.map(|next_pair| view! {
+                                <p>{next_pair}</p>
+                                <button on:click=next_match>"Next Match"</button>
+                            })}
+                        </div>
+                    }
+                }))}
+                <button on:click={move |_| set_menu_page.set(Some(MenuPage::Main))}>
+                    "Main Menu"
+                </button>
+            </div>
+        </div>
+    }
+}
+
+/// The current score of every player, in the player's color, and the boost that the human players
+/// have left.
+#[component]
+fn Scoreboard(game_state: ReadSignal<game::GameState>) -> impl IntoView {
+    // only the scores and colors matter, so the scores don't rerender on every step
+    let scores = Memo::new(move |_| {
+        game_state.with(|s| s.players.iter().map(|p| (p.score, p.color)).collect::<Vec<_>>())
+    });
+    let winning_score = Memo::new(move |_| game_state.with(|s| s.winning_score()));
+    let survival = memo!(game_state.survival);
+    let practice = memo!(game_state.practice);
+    // bots don't hold a boost input, so only the boost of human players is shown
+    let boosts = Memo::new(move |_| {
+        game_state.with(|s| {
+            s.players
+                .iter()
+                .filter(|p| !matches!(p.controller, game::Controller::Bot(_)))
+                .map(|p| (p.boost_charge, p.color))
+                .collect::<Vec<_>>()
+        })
+    });
+
+    view! {
+        <div class="rounds" title=move || if survival.get() {
+            "Last as many rounds as you can".to_string()
+        } else if practice.get() {
+            "Press R to clear your trail".to_string()
+        } else {
+            format!("First to {} points", winning_score.get())
+        }>
+            {move || if practice.get() {
+                "Practice".into_any()
+            } else if survival.get() {
+                // the human is the first player, and the only one that scores
+                scores.get().first().map(|(score, color)| view! {
+                    <span style:color={color.to_string()}>{format!("Round {}", score + 1)}</span>
+                }).into_any()
+            } else {
+                scores.get().into_iter().enumerate().map(|(i, (score, color))| view! {
+                    {(i > 0).then_some(" - ")}
+                    <span style:color={color.to_string()}>{score}</span>
+                }).collect_view().into_any()
+            }}
+            <div class="boost-meters">
+                {move || boosts.get().into_iter().map(|(charge, color)| view! {
+                    <span class="boost-meter" title="Boost">
+                        <span
+                            style:width={format!("{}%", charge * 100 / game::MAX_BOOST_CHARGE)}
+                            style:background-color={color.to_string()}
+                        ></span>
+                    </span>
+                }).collect_view()}
+            </div>
+        </div>
     }
 }
 
@@ -174,131 +1501,512 @@ fn App() -> impl IntoView {
     // signals
     let (menu_page, set_menu_page) = signal(Some(MenuPage::Main));
     let (debug_mode, set_debug_mode) = signal(false);
+    let (debug_metrics, set_debug_metrics) = signal(DebugMetrics::default());
     let (is_fullscreen, set_is_fullscreen) = signal(use_document().fullscreen().unwrap());
-    let (game_state, set_game_state) = signal(GameState::new(0, 6));
+    let settings = Settings::load();
+    let (muted, set_muted) = signal(settings.muted);
+    let (music_volume, set_music_volume) = signal(settings.music_volume);
+    let (has_interacted, set_has_interacted) = signal(false);
+    let (key_bindings, set_key_bindings) = signal(settings.key_bindings);
+    let (pending_binding, set_pending_binding) = signal(None::<PendingBinding>);
+    let (options, set_options) = signal(settings.options);
+    let (has_saved_game, set_has_saved_game) = signal(load_game().is_some());
+    let (game_state, set_game_state) = signal(demo_game(&options.get_untracked()));
+    let (is_demo, set_is_demo) = signal(true);
+    let (initial_state, set_initial_state) = signal(None::<GameState>);
+    let (last_game, set_last_game) = signal(None::<GameState>);
+    let (round_start, set_round_start) = signal(None::<GameState>);
+    let (tournament, set_tournament) = signal(None::<Tournament>);
+    let online = StoredValue::new_local(None::<OnlineGame>);
+    let (online_status, set_online_status) = signal(None::<OnlineStatus>);
     let game_phase = memo!(game_state.phase);
     let max_score = memo!(game_state.max_score);
     let active_player = memo!(game_state.active_player);
 
     // variables
     let canvas_ref = NodeRef::<Canvas>::new();
-    let width = game_state.get().grid_width;
-    let height = game_state.get().grid_height;
-    let mut grid = layout::Grid::new(width, height, &game_state.get());
-
-    Effect::new(move || match game_phase.get() {
-        game::Phase::Step => {
-            use_interval_fn(
-                move || {
-                    set_game_state.update(|s| s.tick());
-                },
-                150,
-            );
-        }
-        game::Phase::Score => {
-            use_interval_fn(move || set_game_state.update(|s| s.tick()), 2000);
-        }
-        game::Phase::GameOver => {
-            set_menu_page.set(Some(MenuPage::Main));
+    let mut grid = game_state.with_untracked(|s| layout::Grid::new(s.grid_width, s.grid_height, s));
+
+    // in an online game, the next tick is only played once the other player's input for it is
+    // there, and a lost connection pauses the game when it runs out of input
+    let is_online = move || online.with_value(|o| o.as_ref().is_some_and(|o| o.lockstep.is_some()));
+    let advance = move || {
+        let mut ticked = false;
+        set_game_state.maybe_update(|s| {
+            s.explain_bots = debug_mode.get_untracked();
+            let advanced = online.try_update_value(|o| match o {
+                Some(OnlineGame { connection, lockstep: Some(lockstep) }) => {
+                    for input in lockstep.take_local_input(s) {
+                        connection.send(&netplay::Message::Input(input));
+                    }
+                    lockstep.advance(s)
+                }
+                _ => {
+                    s.tick();
+                    true
+                }
+            });
+
+            let advanced = advanced.unwrap_or(false);
+            ticked = advanced;
+            if !advanced
+                && online_status.get_untracked() == Some(OnlineStatus::Disconnected)
+                && s.phase != game::Phase::GameOver
+            {
+                s.set_phase(game::Phase::Paused);
+                return true;
+            }
+            advanced
+        });
+        ticked
+    };
+
+    // a single clock at a fixed rate drives the game, which ticks when the clock says it's time
+    // for the phase that it is in
+    let game_clock = StoredValue::new(clock::Clock::default());
+    if CLOCK_RUNNING.replace(true) {
+        log!("Not starting another game clock, because one is running already");
+    } else {
+        let clock_timer = use_interval_fn(
+            move || {
+                let is_due = game_state.with_untracked(|s| {
+                    game_clock.try_update_value(|c| c.run(CLOCK_INTERVAL_MS, s))
+                });
+                if is_due != Some(true) {
+                    return;
+                }
+
+                let is_moving = matches!(
+                    game_phase.get_untracked(),
+                    game::Phase::Step | game::Phase::Collision
+                );
+                if debug_mode.get_untracked() && is_moving {
+                    set_debug_metrics.update(|m| m.record_tick(now()));
+                }
+                // an online game that waits for the other player tries again on the next run
+                if advance() {
+                    game_clock.update_value(clock::Clock::ticked);
+                }
+            },
+            u64::from(CLOCK_INTERVAL_MS),
+        );
+        // stop the timer explicitly, so that the next clock never runs alongside this one
+        on_cleanup(move || {
+            (clock_timer.pause)();
+            CLOCK_RUNNING.set(false);
+        });
+    }
+
+    Effect::new(move || {
+        if game_phase.get() == game::Phase::GameOver {
+            // the game in the background of the menu can end too, which shouldn't interrupt
+            if menu_page.get_untracked().is_none() {
+                set_last_game.set(Some(game_state.get_untracked()));
+
+                // the other client has all the input that it needs to finish the game too
+                if is_online() {
+                    online.set_value(None);
+                    set_online_status.set(None);
+                }
+
+                // replays were already counted when they were played, and survival games go in
+                // the high scores instead of the statistics
+                game_state.with_untracked(|s| {
+                    if s.replay.is_none() && s.survival {
+                        let mut high_scores = stats::HighScores::load();
+                        if high_scores.record(s).is_some() {
+                            high_scores.save();
+                        }
+                    } else if s.replay.is_none() {
+                        let mut stats = stats::Stats::load();
+                        stats.record(s);
+                        stats.save();
+                    }
+                });
+
+                // a replay is not a match of the tournament, even if it was one before
+                let is_match = tournament.with_untracked(Option::is_some)
+                    && game_state.with_untracked(|s| s.replay.is_none());
+                if is_match {
+                    set_tournament.update(|t| {
+                        if let Some(t) = t {
+                            game_state.with_untracked(|s| t.record(s));
+                        }
+                    });
+                    set_menu_page.set(Some(MenuPage::Standings));
+                } else {
+                    set_menu_page.set(Some(MenuPage::Results));
+                }
+            }
             log!("Game Over");
         }
     });
 
+    // every round starts with a countdown, so that is where the debug mode can rewind to
+    Effect::new(move || {
+        if game_phase.get() == game::Phase::Countdown {
+            set_round_start.set(Some(game_state.get_untracked()));
+        }
+    });
+
+    // attract mode: while the main menu is open, bots play behind it, starting over when they're done
+    Effect::new(move || match menu_page.get() {
+        Some(MenuPage::Main) => {
+            // leaving for the main menu ends the tournament and the online game
+            if tournament.with_untracked(Option::is_some) {
+                set_tournament.set(None);
+            }
+            if online.with_value(Option::is_some) {
+                online.set_value(None);
+                set_online_status.set(None);
+            }
+
+            if !is_demo.get_untracked() || game_phase.get() == game::Phase::GameOver {
+                set_game_state.set(demo_game(&options.get_untracked()));
+                set_is_demo.set(true);
+            }
+        }
+        Some(_) => {}
+        None => set_is_demo.set(false),
+    });
+
+    // crashes and points are only heard in a game that is played, not in the one behind the menu
+    Effect::new(move |previous: Option<game::Phase>| {
+        let phase = game_phase.get();
+        if previous.is_some() && menu_page.get_untracked().is_none() {
+            match phase {
+                game::Phase::Collision => audio::play(audio::Sound::Crash),
+                game::Phase::Score => audio::play(audio::Sound::Score),
+                _ => {}
+            }
+        }
+        phase
+    });
+
+    // a player that is knocked out while the others play on crashes without a collision phase
+    let num_eliminated =
+        Memo::new(move |_| game_state.with(|s| s.players.iter().filter(|p| p.eliminated).count()));
+    Effect::new(move |previous: Option<usize>| {
+        let eliminated = num_eliminated.get();
+        if previous.is_some_and(|previous| eliminated > previous)
+            && menu_page.get_untracked().is_none()
+        {
+            audio::play(audio::Sound::Crash);
+        }
+        eliminated
+    });
+
+    Effect::new(move || audio::set_muted(muted.get()));
+
+    Effect::new(move |previous: Option<()>| {
+        let settings = Settings {
+            options: options.get(),
+            fullscreen: is_fullscreen.get(),
+            muted: muted.get(),
+            music_volume: music_volume.get(),
+            key_bindings: key_bindings.get(),
+        };
+        // the stored settings don't need to be written back right after loading them
+        if previous.is_some() {
+            settings.save();
+        }
+    });
+
+    // browsers refuse fullscreen until the user has interacted with the page, so the game goes
+    // back to fullscreen on the first key press or click
+    Effect::new(move || {
+        if has_interacted.get() && settings.fullscreen && !is_fullscreen.get_untracked() {
+            if let Some(body) = document().body() {
+                let _ = body.request_fullscreen();
+            }
+        }
+    });
+
+    // a key binding that is still waiting is forgotten when the settings are left
+    Effect::new(move || {
+        menu_page.track();
+        set_pending_binding.set(None);
+    });
+
+    // the music only starts after the first key press or click, since browsers block audio before
+    let music = audio::MusicPlayer::new(MUSIC_URL);
+    Effect::new(move || {
+        let Some(music) = &music else {
+            return;
+        };
+        let volume = music_volume.get();
+
+        if !has_interacted.get() || volume == 0.0 {
+            music.stop();
+        } else if menu_page.get().is_some() {
+            music.set_volume(volume * MENU_MUSIC_VOLUME);
+            music.play();
+        } else if matches!(game_phase.get(), game::Phase::Paused | game::Phase::GameOver) {
+            music.stop();
+        } else {
+            music.set_volume(volume);
+            music.play();
+        }
+    });
+
+    let _cleanup = use_event_listener(use_window(), keydown, move |_| {
+        if !has_interacted.get_untracked() {
+            set_has_interacted.set(true);
+        }
+    });
+
+    let _cleanup = use_event_listener(use_window(), pointerdown, move |_| {
+        if !has_interacted.get_untracked() {
+            set_has_interacted.set(true);
+        }
+    });
+
     let _cleanup = use_event_listener(use_window(), keydown, move |e| {
-        let nav_prev = ["ArrowUp", "w"];
-        let nav_next = ["ArrowDown", "s"];
+        let nav_prev = ["ArrowUp", "ArrowLeft", "w", "a"];
+        let nav_next = ["ArrowDown", "ArrowRight", "s", "d"];
         let nav_keys = [nav_prev, nav_next].concat();
+        let activate_keys = ["Enter", " "];
         let key = e.key();
         let ctrl = e.ctrl_key();
 
-        if ctrl && key == "d" {
+        // While a game is on, the keys of the players never do what the browser would do with
+        // them, like scrolling the page on the arrow keys or Space, or finding text as you type.
+        // This includes the keys of the players that aren't in the game, and the moments that the
+        // players can't steer, like the countdown or a replay.
+        let is_playing = menu_page.get_untracked().is_none()
+            && !matches!(game_phase.get_untracked(), game::Phase::Paused | game::Phase::GameOver);
+        if is_playing && key_bindings.with_untracked(|bindings| bindings.is_game_key(&key)) {
+            e.prevent_default();
+        }
+
+        if let Some(pending) = pending_binding.get_untracked() {
+            // Key binding: the next key is bound, unless it's Escape, which cancels
+            let mut is_done = key == "Escape";
+            if !is_done {
+                set_key_bindings.maybe_update(|bindings| {
+                    is_done = bindings.bind(&pending.controller, pending.direction, &key);
+                    is_done
+                });
+            }
+
+            if is_done {
+                set_pending_binding.set(None);
+            } else {
+                set_pending_binding.set(Some(PendingBinding { key_in_use: true, ..pending }));
+            }
+            e.prevent_default();
+        } else if ctrl && key == "d" {
             // Debug mode: Ctrl + D
             set_debug_mode.set(!debug_mode.get());
             e.prevent_default();
-            return;
-        } else if menu_page.get().is_some() && nav_keys.contains(&key.as_str()) {
-            // Menu keyboard input
-            let menu_items: NodeList = use_document()
-                .query_selector_all("button")
-                .unwrap()
-                .unwrap()
-                .dyn_into()
-                .unwrap();
-            let first_item: HtmlElement = menu_items
-                .item(0)
-                .expect("Menu should have at least one item")
-                .dyn_into()
-                .unwrap();
+        } else if debug_mode.get_untracked()
+            && key == "r"
+            && menu_page.get_untracked().is_none()
+            && game_phase.get_untracked() != game::Phase::Paused
+            && !game_state.with_untracked(|s| s.practice)
+        {
+            // Debug mode: R rewinds to the start of the round, including the state of the bots.
+            // Not in a menu or the pause screen, where R may be typed, or in a practice game,
+            // where R clears the trail.
+            if let Some(round_start) = round_start.get_untracked() {
+                set_game_state.set(round_start);
+            }
+            e.prevent_default();
+        } else if (menu_page.get().is_some() || game_phase.get_untracked() == game::Phase::Paused)
+            && (nav_keys.contains(&key.as_str())
+                || activate_keys.contains(&key.as_str())
+                || key == "Tab")
+        {
+            // Menu keyboard input: the focus moves between the items of the menu that is shown,
+            // wrapping around at either end, and Enter or Space activates the focused item. This
+            // doesn't wait for the browser, which would also scroll the page on Space.
+            let items = menu_items();
+            let Some(first_item) = items.first() else {
+                return;
+            };
+            let active: Option<HtmlElement> =
+                document().active_element().and_then(|element| element.dyn_into().ok());
+            let focused = items.iter().position(|item| Some(item) == active.as_ref());
 
-            'focus_next: {
-                match use_active_element().get() {
-                    Some(element) => {
-                        // If the active element is in the list of buttons, go to next or previous
-                        for (i, button) in menu_items.values().into_iter().enumerate() {
-                            let i = i as u32;
-                            if element == button.unwrap().dyn_into().unwrap() {
-                                let next_button = menu_items
-                                    .item(if nav_prev.contains(&key.as_str()) {
-                                        i.checked_sub(1).unwrap_or(menu_items.length() - 1)
-                                    } else if nav_next.contains(&key.as_str()) {
-                                        i + 1
-                                    } else {
-                                        0
-                                    })
-                                    .unwrap_or(menu_items.item(0).unwrap())
-                                    .dyn_into::<HtmlElement>()
-                                    .unwrap();
-                                next_button.focus().unwrap();
-                                e.prevent_default();
-                                break 'focus_next;
-                            }
-                        }
+            // text fields and sliders need the arrow keys, letters and spaces themselves
+            let is_typing = focused.is_some_and(|i| items[i].tag_name() == "INPUT");
+            if is_typing && (nav_keys.contains(&key.as_str()) || key == " ") {
+                return;
+            }
 
-                        // If the active element is not in the menu, focus the first button
-                        first_item.focus().unwrap();
-                    }
-                    None => {
-                        // If there is no active element, focus the first button
-                        first_item.focus().unwrap();
-                    }
-                }
+            let is_prev = nav_prev.contains(&key.as_str()) || (key == "Tab" && e.shift_key());
+            match focused {
+                Some(i) if activate_keys.contains(&key.as_str()) => items[i].click(),
+                Some(i) if is_prev => items[(i + items.len() - 1) % items.len()].focus().unwrap(),
+                Some(i) => items[(i + 1) % items.len()].focus().unwrap(),
+                // if the focus is not in the menu, it starts at the first item
+                None => first_item.focus().unwrap(),
             }
-        } else if game_phase.get_untracked() == game::Phase::Step {
-            // Player keyboard input
+            e.prevent_default();
+        } else if menu_page.get().is_none()
+            && (key == "Escape" || key == "p")
+            && matches!(game_phase.get_untracked(), game::Phase::Step | game::Phase::Paused)
+            && !is_online()
+        {
+            // Pause and resume: Escape or P, except online, where the other player would go on
+            set_game_state.update(|game_state| game_state.toggle_pause());
+            e.prevent_default();
+        } else if menu_page.get().is_none()
+            && key == "r"
+            && game_state.with_untracked(|s| s.practice)
+            && matches!(game_phase.get_untracked(), game::Phase::Step | game::Phase::Collision)
+        {
+            // Clear the trail in a practice game: R
+            set_game_state.update(|game_state| game_state.reset_practice());
+            e.prevent_default();
+        } else if game_phase.get_untracked() == game::Phase::Step
+            && game_state.with_untracked(|s| s.replay.is_none())
+        {
+            // Player keyboard input: every player only listens to the keys of its own controller
             set_game_state.update(|game_state| {
+                key_bindings.with_untracked(|bindings| {
+                    for player in game_state.players.iter_mut() {
+                        if let Some(direction) = bindings.direction(&player.controller, &key) {
+                            handle_action(&e, player, direction);
+                        } else if bindings.is_boost_key(&player.controller, &key) {
+                            player.boosting = true;
+                            e.prevent_default();
+                        }
+                    }
+                });
+            });
+        }
+    });
+
+    // releasing the boost key always ends the boost, whatever the phase of the game
+    let _cleanup = use_event_listener(use_window(), keyup, move |e| {
+        let key = e.key();
+        set_game_state.maybe_update(|game_state| {
+            key_bindings.with_untracked(|bindings| {
+                let mut changed = false;
                 for player in game_state.players.iter_mut() {
-                    match player.controller {
-                        game::Controller::Wasd => match e.key().as_str() {
-                            "w" => handle_action(&e, player, common::Direction::North),
-                            "a" => handle_action(&e, player, common::Direction::West),
-                            "s" => handle_action(&e, player, common::Direction::South),
-                            "d" => handle_action(&e, player, common::Direction::East),
-                            _ => (),
-                        },
-                        game::Controller::Arrows => match e.key().as_str() {
-                            "ArrowUp" => handle_action(&e, player, common::Direction::North),
-                            "ArrowLeft" => handle_action(&e, player, common::Direction::West),
-                            "ArrowDown" => handle_action(&e, player, common::Direction::South),
-                            "ArrowRight" => handle_action(&e, player, common::Direction::East),
-                            _ => (),
-                        },
-                        game::Controller::Bot => (),
-                        _ => unimplemented!(),
+                    if player.boosting && bindings.is_boost_key(&player.controller, &key) {
+                        player.boosting = false;
+                        changed = true;
                     }
                 }
-            });
+                changed
+            })
+        });
+    });
+
+    // gamepads can't send events for their buttons, so they are read on every animation frame
+    let _gamepads = use_raf_fn(move |_| {
+        if game_phase.get_untracked() != game::Phase::Step
+            || game_state.with_untracked(|s| s.replay.is_some())
+        {
+            return;
+        }
+
+        set_game_state.maybe_update(|game_state| {
+            let mut changed = false;
+            for player in game_state.players.iter_mut() {
+                if let game::Controller::Gamepad(index) = player.controller {
+                    let boosting = gamepad::is_boost_pressed(index);
+                    if player.boosting != boosting {
+                        player.boosting = boosting;
+                        changed = true;
+                    }
+
+                    if let Some(direction) = gamepad::direction(index) {
+                        if player.set_direction(direction) {
+                            audio::play(audio::Sound::Turn);
+                            changed = true;
+                        }
+                    }
+                }
+            }
+            changed
+        });
+    });
+
+    let _cleanup = use_event_listener(use_window(), gamepadconnected, move |e| {
+        if let Some(gamepad) = e.gamepad() {
+            log!("Gamepad {} connected", gamepad.index());
+            set_game_state.update(|s| s.connect_gamepad(gamepad.index() as usize));
+        }
+    });
+
+    let _cleanup = use_event_listener(use_window(), gamepaddisconnected, move |e| {
+        if let Some(gamepad) = e.gamepad() {
+            log!("Gamepad {} disconnected", gamepad.index());
+            set_game_state.update(|s| s.disconnect_gamepad(gamepad.index() as usize));
+        }
+    });
+
+    let is_playing = move || {
+        game_phase.get() == game::Phase::Step
+            && menu_page.get().is_none()
+            && game_state.with(|s| s.replay.is_none())
+    };
+    let steer = move |direction| {
+        if is_playing() {
+            set_game_state.update(|s| steer_touch_player(s, direction));
+        }
+    };
+
+    // swipes steer the player: the direction is decided when the finger is lifted
+    let touch_start = StoredValue::new(None::<(f64, f64)>);
+
+    let _cleanup = use_event_listener(use_window(), touchstart, move |e| {
+        if let Some(touch) = e.changed_touches().get(0) {
+            touch_start.set_value(Some((touch.client_x() as f64, touch.client_y() as f64)));
         }
     });
 
+    let _cleanup = use_event_listener(use_window(), touchend, move |e| {
+        let (Some((start_x, start_y)), Some(touch)) =
+            (touch_start.get_value(), e.changed_touches().get(0))
+        else {
+            return;
+        };
+
+        touch_start.set_value(None);
+        let dx = touch.client_x() as f64 - start_x;
+        let dy = touch.client_y() as f64 - start_y;
+
+        if let Some(direction) = swipe_direction(dx, dy) {
+            steer(direction);
+        }
+    });
+
+    // while playing, swipes should not scroll the page, which requires a non-passive listener
+    let _cleanup = use_event_listener_with_options(
+        use_window(),
+        touchmove,
+        move |e| {
+            if is_playing() {
+                e.prevent_default();
+            }
+        },
+        UseEventListenerOptions::default().passive(false),
+    );
+
+    // the canvas size is read while drawing, so a resized window only needs another redraw
+    let resized = Trigger::new();
+    let _cleanup = use_event_listener(use_window(), resize, move |_| resized.notify());
+
     let _cleanup = use_event_listener(use_document(), fullscreenchange, move |_| {
         set_is_fullscreen.set(use_document().fullscreen().unwrap());
     });
 
-    Effect::new(move || {
+    Effect::new(move |previous: Option<GameState>| {
+        let game_state = game_state.get();
+        resized.track();
+
+        // update grid with game state, keeping it in sync even while there is no canvas
+        match &previous {
+            Some(previous) => grid.apply_delta(previous, &game_state),
+            None => grid.reset(&game_state),
+        }
+
         if let Some(canvas) = canvas_ref.get() {
-            let rect = canvas.get_bounding_client_rect();
-            canvas.set_width(rect.width() as u32);
-            canvas.set_height(rect.height() as u32);
+            fit_canvas(&canvas);
 
             let c = canvas
                 .get_context("2d")
@@ -307,31 +2015,128 @@ fn App() -> impl IntoView {
                 .dyn_into::<CanvasRenderingContext2d>()
                 .unwrap();
 
-            // update grid with game state
-            // TODO: don't replace the whole grid on every update
-            grid.reset(&game_state.get());
+            let start = now();
+            let options = options.get();
+            render::draw_board(&c, &grid, &canvas, options.trail_style, &options.theme());
+            if game_state.arena_mode == ArenaMode::Wrap {
+                render::draw_wraps(&c, &grid, &canvas, &game_state);
+            }
+            if options.crt_effect {
+                render::draw_crt(&c, &canvas);
+            }
+            if debug_mode.get_untracked() {
+                render::draw_grid_overlay(&c, &grid, &canvas, &options.theme());
+                render::draw_bot_decisions(&c, &grid, &canvas, &game_state, &options.theme());
+            }
+            let render_time = now() - start;
 
-            render::draw_board(&c, grid.get_data(), &canvas);
+            if debug_mode.get_untracked() {
+                set_debug_metrics.update(|m| m.record_render_time(render_time));
+            }
         }
+
+        // the debug view shows the same grid that is drawn, and only while it is shown
+        if debug_mode.get_untracked() {
+            set_debug_metrics.update(|m| {
+                m.segments = game_state.players.iter().map(|p| p.segments.len()).sum();
+                m.occupied_cells = game_state.occupied_cells();
+                m.grid = format!("{:?}", grid);
+            });
+        }
+
+        game_state
     });
 
     view! {
         <Show when=move || !debug_mode.get()
             fallback=move || view! {
-                <div>
+                <canvas node_ref={canvas_ref}></canvas>
+                <div class="debug">
                     <p>max_score: {max_score}</p>
-                    <pre style="text-align:left">{format!("{:#?}", layout::Grid::new(width, height, &game_state.get()))}</pre>
                     <p>active_player: {active_player}</p>
-                    <p>phase: {format!("{:?}", game_phase.get())}</p>
+                    <p>phase: {move || format!("{:?}", game_phase.get())}</p>
+                    {move || debug_metrics.with(|m| view! {
+                        <p>
+                            {format!(
+                                "render time: {:.2} ms (average of {} frames)",
+                                m.average_render_time(),
+                                m.render_times.len(),
+                            )}
+                        </p>
+                        <p>
+                            {match m.tick_interval {
+                                Some(interval) => format!("tick interval: {:.0} ms", interval),
+                                None => "tick interval: -".to_string(),
+                            }}
+                        </p>
+                        <p>{format!("segments: {}", m.segments)}</p>
+                        <p>{format!("occupied cells: {}", m.occupied_cells)}</p>
+                        <pre>{m.grid.clone()}</pre>
+                    })}
                 </div>
             }>
                 <canvas node_ref={canvas_ref}></canvas>
                 <div>
-                    <div class="rounds">{max_score}</div>
+                    <Scoreboard game_state />
                 </div>
+                <Show when=is_playing>
+                    <div class="touch-controls">
+                        <button style="grid-area: up" on:click={move |_| steer(common::Direction::North)}>"▲"</button>
+                        <button style="grid-area: left" on:click={move |_| steer(common::Direction::West)}>"◀"</button>
+                        <button style="grid-area: right" on:click={move |_| steer(common::Direction::East)}>"▶"</button>
+                        <button style="grid-area: down" on:click={move |_| steer(common::Direction::South)}>"▼"</button>
+                    </div>
+                </Show>
+                <Show when=move || game_phase.get() == game::Phase::Paused && menu_page.get().is_none()>
+                    <div class="center">
+                        <div class="menu">
+                            <h1>{move || if online_status.get().is_some() { "Connection Lost" } else { "Paused" }}</h1>
+                            <Show when=move || online_status.get().is_none()>
+                                <div class="items">
+                                    <button on:click={move |_| set_game_state.update(|s| s.toggle_pause())}>
+                                        "Resume"
+                                    </button>
+                                    <button on:click={move |_| {
+                                        save_game(&game_state.get_untracked());
+                                        set_has_saved_game.set(true);
+                                    }}>
+                                        "Save"
+                                    </button>
+                                </div>
+                            </Show>
+                            <button on:click={move |_| set_menu_page.set(Some(MenuPage::Main))}>
+                                "Main Menu"
+                            </button>
+                        </div>
+                    </div>
+                </Show>
                 <Show when=move || menu_page.get().is_some()>
                     <div>
-                        <Menu menu_page set_menu_page set_game_state is_fullscreen />
+                        <Menu
+                            menu_page
+                            set_menu_page
+                            game_state
+                            set_game_state
+                            initial_state
+                            set_initial_state
+                            last_game
+                            is_fullscreen
+                            muted
+                            set_muted
+                            music_volume
+                            set_music_volume
+                            key_bindings
+                            pending_binding
+                            set_pending_binding
+                            options
+                            set_options
+                            has_saved_game
+                            tournament
+                            set_tournament
+                            online
+                            online_status
+                            set_online_status
+                        />
                     </div>
                 </Show>
         </Show>
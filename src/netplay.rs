@@ -0,0 +1,133 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use serde::{Deserialize, Serialize};
+use web_sys::{
+    wasm_bindgen::{closure::Closure, JsCast},
+    MessageEvent, WebSocket,
+};
+
+use cordon_core::lockstep::Input;
+
+use crate::storage;
+
+/// The local storage key under which the address of the relay server is kept.
+const SERVER_URL_KEY: &str = "cordon.server_url";
+
+/// The local storage key under which the rollback setting is kept.
+const ROLLBACK_KEY: &str = "cordon.rollback";
+
+/// The messages that the clients send each other.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Message {
+    /// The sender is connected and waits for the game to start. Whichever client connects first
+    /// misses the message of the other, so the guest answers the host with another one.
+    Join,
+    /// The host starts the game, from the specified state as JSON. Both clients step the same
+    /// state with the same inputs, which keeps them in sync.
+    Start(String),
+    /// The input of the sender's player.
+    Input(Input),
+}
+
+
+/// A connection to the other client, through a relay server that passes every message on to the
+/// other clients that are connected to the same address.
+pub struct Connection {
+    socket: WebSocket,
+    _on_open: Closure<dyn FnMut()>,
+    _on_message: Closure<dyn FnMut(MessageEvent)>,
+    _on_close: Closure<dyn FnMut()>,
+}
+
+impl Connection {
+    /// Connect to the relay server at the specified address, announcing this client as soon as
+    /// the connection is open. Every message that arrives goes to `on_message`, and `on_close` is
+    /// called when the connection is lost.
+    pub fn open(
+        url: &str,
+        mut on_message: impl FnMut(Message) + 'static,
+        on_close: impl FnMut() + 'static,
+    ) -> Option<Self> {
+        let socket = WebSocket::new(url).ok()?;
+
+        let open_socket = socket.clone();
+        let on_open = Closure::<dyn FnMut()>::new(move || send(&open_socket, &Message::Join));
+
+        let on_message = Closure::<dyn FnMut(MessageEvent)>::new(move |e: MessageEvent| {
+            let message = e.data().as_string().and_then(|json| serde_json::from_str(&json).ok());
+            if let Some(message) = message {
+                on_message(message);
+            }
+        });
+
+        let on_close = Closure::<dyn FnMut()>::new(on_close);
+
+        socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+        socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+        socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+
+        Some(Connection {
+            socket,
+            _on_open: on_open,
+            _on_message: on_message,
+            _on_close: on_close,
+        })
+    }
+
+    /// Send a message to the other client.
+    pub fn send(&self, message: &Message) {
+        send(&self.socket, message);
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        // leaving on purpose is not a lost connection
+        self.socket.set_onclose(None);
+        self.socket.set_onmessage(None);
+        let _ = self.socket.close();
+    }
+}
+
+fn send(socket: &WebSocket, message: &Message) {
+    let json = serde_json::to_string(message).expect("message should be serializable");
+    // a message that can't be sent is lost with the connection, which closes it
+    let _ = socket.send_with_str(&json);
+}
+
+/// The address of the relay server that was used last, if any.
+pub fn server_url() -> String {
+    storage::get(SERVER_URL_KEY).unwrap_or_default()
+}
+
+/// Remember the address of the relay server in local storage.
+pub fn set_server_url(url: &str) {
+    storage::set(SERVER_URL_KEY, url);
+}
+
+/// Whether online games play ahead of the other player, and roll back when the guess was wrong.
+pub fn is_rollback_enabled() -> bool {
+    storage::get(ROLLBACK_KEY).as_deref() == Some("true")
+}
+
+/// Turn rollback on or off, and remember the choice in local storage.
+pub fn set_rollback_enabled(enabled: bool) {
+    storage::set(ROLLBACK_KEY, if enabled { "true" } else { "false" });
+}
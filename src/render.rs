@@ -1,105 +1,396 @@
-use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use serde::{Deserialize, Serialize};
+use web_sys::{js_sys, wasm_bindgen::JsValue, CanvasRenderingContext2d, HtmlCanvasElement};
 
-use crate::{
-    common::{self, Color},
+use cordon_core::{
+    common::{self, Color, Direction, Palette, TrailPattern},
+    game::{GameState, PickupEffect, COLLISION_FRAMES},
     layout,
 };
+use std::{
+    f64::consts::PI,
+    fmt::{self, Display, Formatter},
+};
+
+/// How the corners of a trail are drawn.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum TrailStyle {
+    /// Corners are right angles, like bricks laid on a grid.
+    #[default]
+    Sharp,
+    /// Corners are quarter circles, so the trail looks like one smooth curve.
+    Rounded,
+}
+
+impl TrailStyle {
+    /// The next trail style, wrapping around from the last to the first.
+    pub fn next(&self) -> Self {
+        match self {
+            TrailStyle::Sharp => TrailStyle::Rounded,
+            TrailStyle::Rounded => TrailStyle::Sharp,
+        }
+    }
+}
 
+impl Display for TrailStyle {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            TrailStyle::Sharp => write!(f, "Sharp"),
+            TrailStyle::Rounded => write!(f, "Rounded"),
+        }
+    }
+}
+
+/// The colors of the board, apart from the players and the pickups.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Theme {
+    /// Behind the arena, and in the margins around it.
+    pub background: Color,
+    /// The line through walls and trails, and the letters on the board.
+    pub outline: Color,
+    /// Where a player crashed.
+    pub collision: Color,
+    /// The lines and numbers of the debug grid, and the shimmer of players that pass through walls.
+    pub grid: Color,
+}
+
+/// The built-in themes, which are chosen in the settings.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub enum ThemePreset {
+    #[default]
+    Dark,
+    Light,
+    Neon,
+}
+
+impl ThemePreset {
+    /// The colors of this theme. Crashes are highlighted in the collision color of the palette,
+    /// so that they stay easy to see with the color-blind safe palette.
+    pub fn theme(&self, palette: Palette) -> Theme {
+        match self {
+            ThemePreset::Dark => Theme {
+                background: Color::black(),
+                outline: Color::black(),
+                collision: palette.collision(),
+                grid: Color::white(),
+            },
+            ThemePreset::Light => Theme {
+                background: Color::from_rgb8(235, 235, 230),
+                outline: Color::from_rgb8(40, 40, 40),
+                collision: palette.collision(),
+                grid: Color::black(),
+            },
+            ThemePreset::Neon => Theme {
+                background: Color::from_rgb8(10, 0, 25),
+                outline: Color::white(),
+                collision: match palette {
+                    Palette::Standard => Color::from_rgb8(255, 0, 200),
+                    Palette::ColorBlind => palette.collision(),
+                },
+                grid: Color::from_rgb8(0, 255, 255),
+            },
+        }
+    }
+
+    /// The next theme, wrapping around from the last to the first.
+    pub fn next(&self) -> Self {
+        match self {
+            ThemePreset::Dark => ThemePreset::Light,
+            ThemePreset::Light => ThemePreset::Neon,
+            ThemePreset::Neon => ThemePreset::Dark,
+        }
+    }
+}
+
+impl Display for ThemePreset {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ThemePreset::Dark => write!(f, "Dark"),
+            ThemePreset::Light => write!(f, "Light"),
+            ThemePreset::Neon => write!(f, "Neon"),
+        }
+    }
+}
+
+/// The width of the lines through walls and players, as a part of the size of a cell, so that the
+/// board looks the same at every resolution.
+const LINE_WIDTH: f64 = 0.12;
+
+/// The width in pixels of the lines through walls and players, in cells of the specified size.
+fn line_width(cell_size: f64) -> f64 {
+    (cell_size * LINE_WIDTH).max(1.0)
+}
+
+/// The number of dashes or dots along a line across a whole cell.
+const PATTERN_REPEATS: f64 = 2.0;
+
+/// Set up the dashes or dots of the next lines, for a line with the specified length across a
+/// whole cell. The pattern repeats a whole number of times along such a line, and it is symmetric,
+/// so that the lines in neighboring cells join up, whichever way they were drawn.
+fn set_line_pattern(c: &CanvasRenderingContext2d, pattern: TrailPattern, length: f64) {
+    let period = length / PATTERN_REPEATS;
+    let (segments, offset, cap) = match pattern {
+        TrailPattern::Solid => (vec![], 0.0, "butt"),
+        // a dash is centered on the start of each period
+        TrailPattern::Dashed => (vec![period * 0.5, period * 0.5], period * 0.25, "butt"),
+        // the dots are dashes without length, which the round line cap turns into circles
+        TrailPattern::Dotted => (vec![0.0, period], 0.0, "round"),
+    };
+    let segments: js_sys::Array = segments.into_iter().map(JsValue::from).collect();
+
+    c.set_line_dash(&segments).unwrap();
+    c.set_line_dash_offset(offset);
+    c.set_line_cap(cap);
+}
+
+/// Draw the line through a wall in a square cell, in the current stroke style, with dots in the
+/// current fill style. The cell's background is expected to be drawn already.
 fn draw_wall(
     wall_type: &layout::WallType,
-    color: &common::Color,
+    style: TrailStyle,
+    pattern: TrailPattern,
+    c: &CanvasRenderingContext2d,
+    x: f64,
+    y: f64,
+    size: f64,
+) {
+    c.set_line_width(line_width(size));
+
+    let half_size = size * 0.5;
+
+    // each rounded corner is a quarter circle around the cell corner on the inside of the bend
+    let arc = match wall_type {
+        layout::WallType::CornerTopLeft => Some((x + size, y + size, PI, 1.5 * PI)),
+        layout::WallType::CornerTopRight => Some((x, y + size, 1.5 * PI, 2.0 * PI)),
+        layout::WallType::CornerBottomLeft => Some((x + size, y, 0.5 * PI, PI)),
+        layout::WallType::CornerBottomRight => Some((x, y, 0.0, 0.5 * PI)),
+        _ => None,
+    }
+    .filter(|_| style == TrailStyle::Rounded);
+
+    if let Some((center_x, center_y, start, end)) = arc {
+        set_line_pattern(c, pattern, 0.5 * PI * half_size);
+        c.begin_path();
+        c.arc(center_x, center_y, half_size, start, end).unwrap();
+        c.stroke();
+    } else {
+        set_line_pattern(c, pattern, size);
+        match wall_type {
+            layout::WallType::Horizontal => {
+                c.begin_path();
+                c.move_to(x, y + half_size);
+                c.line_to(x + size, y + half_size);
+                c.stroke();
+            }
+            layout::WallType::Vertical => {
+                c.begin_path();
+                c.move_to(x + half_size, y);
+                c.line_to(x + half_size, y + size);
+                c.stroke();
+            }
+            layout::WallType::CornerTopLeft => {
+                c.begin_path();
+                c.move_to(x + half_size, y + size);
+                c.line_to(x + half_size, y + half_size);
+                c.line_to(x + size, y + half_size);
+                c.stroke();
+            }
+            layout::WallType::CornerTopRight => {
+                c.begin_path();
+                c.move_to(x, y + half_size);
+                c.line_to(x + half_size, y + half_size);
+                c.line_to(x + half_size, y + size);
+                c.stroke();
+            }
+            layout::WallType::CornerBottomLeft => {
+                c.begin_path();
+                c.move_to(x + half_size, y);
+                c.line_to(x + half_size, y + half_size);
+                c.line_to(x + size, y + half_size);
+                c.stroke();
+            }
+            layout::WallType::CornerBottomRight => {
+                c.begin_path();
+                c.move_to(x, y + half_size);
+                c.line_to(x + half_size, y + half_size);
+                c.line_to(x + half_size, y);
+                c.stroke();
+            }
+            layout::WallType::End(direction) => {
+                draw_spokes(&[*direction], c, x, y, size, size);
+                draw_dot(c, x + half_size, y + half_size);
+            }
+            layout::WallType::Tee(missing) => {
+                let directions: Vec<Direction> =
+                    Direction::ALL.into_iter().filter(|d| d != missing).collect();
+                draw_spokes(&directions, c, x, y, size, size);
+            }
+            layout::WallType::Cross => {
+                draw_spokes(&Direction::ALL, c, x, y, size, size);
+            }
+            layout::WallType::Single => {
+                draw_dot(c, x + half_size, y + half_size);
+            }
+        }
+    }
+
+    set_line_pattern(c, TrailPattern::Solid, size);
+}
+
+/// Draw lines from the middle of a cell to the middle of each of the specified sides.
+fn draw_spokes(
+    directions: &[Direction],
     c: &CanvasRenderingContext2d,
     x: f64,
     y: f64,
     width: f64,
     height: f64,
 ) {
-    c.set_fill_style_str(&color.to_string());
-    c.fill_rect(x, y, width, height);
+    let x_mid = x + width * 0.5;
+    let y_mid = y + height * 0.5;
 
-    c.set_stroke_style_str(&Color::black().to_string());
-    c.set_line_width(4.0);
+    c.begin_path();
+    for direction in directions {
+        let (to_x, to_y) = match direction {
+            Direction::North => (x_mid, y),
+            Direction::South => (x_mid, y + height),
+            Direction::West => (x, y_mid),
+            Direction::East => (x + width, y_mid),
+        };
+        c.move_to(x_mid, y_mid);
+        c.line_to(to_x, to_y);
+    }
+    c.stroke();
+}
 
-    let half_width = width * 0.5;
-    let half_height = height * 0.5;
+/// Draw a dot the width of a wall line, for the ends of walls, in the current fill style.
+fn draw_dot(c: &CanvasRenderingContext2d, x_mid: f64, y_mid: f64) {
+    c.begin_path();
+    c.arc(x_mid, y_mid, c.line_width(), 0.0, 2.0 * PI).unwrap();
+    c.fill();
+}
 
-    match wall_type {
-        layout::WallType::Horizontal => {
-            c.begin_path();
-            c.move_to(x, y + half_height);
-            c.line_to(x + width, y + half_height);
-            c.stroke();
-        }
-        layout::WallType::Vertical => {
-            c.begin_path();
-            c.move_to(x + half_width, y);
-            c.line_to(x + half_width, y + height);
-            c.stroke();
-        }
-        layout::WallType::CornerTopLeft => {
-            c.begin_path();
-            c.move_to(x + half_width, y + height);
-            c.line_to(x + half_width, y + half_height);
-            c.line_to(x + width, y + half_height);
-            c.stroke();
-        }
-        layout::WallType::CornerTopRight => {
-            c.begin_path();
-            c.move_to(x, y + half_height);
-            c.line_to(x + half_width, y + half_height);
-            c.line_to(x + half_width, y + height);
-            c.stroke();
-        }
-        layout::WallType::CornerBottomLeft => {
-            c.begin_path();
-            c.move_to(x + half_width, y);
-            c.line_to(x + half_width, y + half_height);
-            c.line_to(x + width, y + half_height);
-            c.stroke();
-        }
-        layout::WallType::CornerBottomRight => {
-            c.begin_path();
-            c.move_to(x, y + half_height);
-            c.line_to(x + half_width, y + half_height);
-            c.line_to(x + half_width, y);
-            c.stroke();
+/// Draw a flickering outline around a cell, for players that can pass through a wall.
+fn draw_shimmer(
+    c: &CanvasRenderingContext2d,
+    theme: &Theme,
+    x: f64,
+    y: f64,
+    width: f64,
+    height: f64,
+) {
+    let phase = web_sys::js_sys::Date::now() / 150.0;
+    let brightness = 0.5 + 0.5 * phase.sin();
+
+    c.set_stroke_style_str(&theme.background.mix(&theme.grid, brightness).to_string());
+    // the outline is half as wide as a wall line, and it stays inside the cell
+    let line_width = line_width(width.min(height)) * 0.5;
+    c.set_line_width(line_width);
+    c.stroke_rect(
+        x + line_width * 0.5,
+        y + line_width * 0.5,
+        width - line_width,
+        height - line_width,
+    );
+}
+
+/// Draw one frame of the explosion at a crash site, centered on the cell at (`x_mid`, `y_mid`).
+/// The burst grows with every frame, while its color fades from the player's color to the
+/// collision color of the theme and becomes more transparent.
+fn draw_explosion(
+    frame: u32,
+    color: &Color,
+    theme: &Theme,
+    c: &CanvasRenderingContext2d,
+    x_mid: f64,
+    y_mid: f64,
+    cell_size: f64,
+) {
+    let progress = (frame + 1) as f64 / COLLISION_FRAMES as f64;
+    let radius = cell_size * (0.5 + 1.5 * progress);
+    let burst_color = color
+        .mix(&theme.collision, progress)
+        .with_alpha(1.0 - 0.5 * progress);
+    let num_rays = 8;
+
+    c.set_fill_style_str(&burst_color.to_string());
+    c.begin_path();
+    for i in 0..(num_rays * 2) {
+        // alternate between the tips of the rays and the points in between
+        let angle = i as f64 * PI / num_rays as f64;
+        let r = if i % 2 == 0 { radius } else { radius * 0.4 };
+        let px = x_mid + r * angle.cos();
+        let py = y_mid + r * angle.sin();
+        if i == 0 {
+            c.move_to(px, py);
+        } else {
+            c.line_to(px, py);
         }
     }
+    c.close_path();
+    c.fill();
+}
+
+/// The size of a cell and the position of the top left corner of the arena on the canvas.
+fn board_area(grid: &layout::Grid, canvas: &HtmlCanvasElement) -> (f64, f64, f64) {
+    let canvas_width = canvas.width() as f64;
+    let canvas_height = canvas.height() as f64;
+
+    let columns = grid.width() as f64;
+    let rows = grid.height() as f64;
+
+    // Cells are square and a whole number of pixels, so the arena doesn't get stretched. The
+    // playfield is centered, leaving margins on the sides that don't fit exactly.
+    let cell_size = (canvas_width / columns).min(canvas_height / rows).floor();
+
+    let offset_x = ((canvas_width - cell_size * columns) / 2.0).floor();
+    let offset_y = ((canvas_height - cell_size * rows) / 2.0).floor();
+    (cell_size, offset_x, offset_y)
 }
 
 pub fn draw_board(
     c: &CanvasRenderingContext2d,
-    grid_data: &Vec<Vec<layout::Cell>>,
+    grid: &layout::Grid,
     canvas: &HtmlCanvasElement,
+    trail_style: TrailStyle,
+    theme: &Theme,
 ) {
     let canvas_width = canvas.width() as f64;
     let canvas_height = canvas.height() as f64;
 
-    // Account for the fact that the canvas is not a perfect multiple of the grid size
-    let draw_width = (canvas_width / 32.0).floor() * 32.0;
-    let draw_height = (canvas_height / 28.0).floor() * 28.0;
+    let (cell_size, offset_x, offset_y) = board_area(grid, canvas);
+    let cell_width = cell_size;
+    let cell_height = cell_size;
 
-    let cell_width = draw_width / grid_data[0].len() as f64;
-    let cell_height = draw_height / grid_data.len() as f64;
+    c.set_fill_style_str(&theme.background.to_string());
+    c.fill_rect(0.0, 0.0, canvas_width, canvas_height);
 
-    c.set_fill_style_str(&Color::black().to_string());
-    c.fill_rect(0.0, 0.0, canvas_width as f64, canvas_height as f64);
-
-    for (row_i, row) in grid_data.iter().enumerate() {
-        for (cell_i, cell) in row.iter().enumerate() {
-            let x = cell_i as f64 * cell_width;
+    for row_i in 0..grid.height() {
+        for cell_i in 0..grid.width() {
+            let x = offset_x + cell_i as f64 * cell_width;
             let x_mid = x + cell_width * 0.5;
             let x_high = x + cell_width;
-            let y = row_i as f64 * cell_height + cell_height;
+            let y = offset_y + row_i as f64 * cell_height + cell_height;
             let y_mid = y - cell_height * 0.5;
             let y_high = y - cell_height;
 
-            match cell {
-                layout::Cell::Wall(wall_type, color) => {
-                    draw_wall(wall_type, color, c, x, y_high, cell_width, cell_height)
+            match grid.get(cell_i, row_i) {
+                layout::Cell::Wall(wall_type, color, glow, pattern) => {
+                    // older segments fade into the dark, like the ribbon behind a light cycle
+                    let color = color.darken().mix(&color, glow).with_alpha(0.6 + 0.4 * glow);
+                    c.set_fill_style_str(&color.to_string());
+                    c.fill_rect(x, y_high, cell_width, cell_height);
+
+                    let outline = theme.outline.to_string();
+                    c.set_stroke_style_str(&outline);
+                    c.set_fill_style_str(&outline);
+                    draw_wall(&wall_type, trail_style, pattern, c, x, y_high, cell_size);
                 }
-                layout::Cell::Player(direction, color) => {
-                    let line_width = 4.0;
+                layout::Cell::Player(direction, color, is_ghost) => {
+                    if is_ghost {
+                        draw_shimmer(c, theme, x, y_high, cell_width, cell_height);
+                    }
+
+                    let line_width = line_width(cell_size);
                     let margin = line_width / 2.0;
                     c.set_line_width(line_width);
                     c.set_stroke_style_str(&color.to_string());
@@ -130,19 +421,32 @@ pub fn draw_board(
 
                     c.stroke();
                 }
-                layout::Cell::Collision => {
-                    c.set_fill_style_str(&Color::yellow().to_string());
+                layout::Cell::Collision | layout::Cell::Explosion(..) => {
+                    c.set_fill_style_str(&theme.collision.to_string());
                     c.fill_rect(x, y_high, cell_width, cell_height);
                 }
+                layout::Cell::Pickup(effect) => {
+                    let color = match effect {
+                        PickupEffect::SpeedBoost => Color::yellow(),
+                        PickupEffect::ExtraPoint => Color::green(),
+                        PickupEffect::ShrinkTrail => Color::blue(),
+                        PickupEffect::Ghost => Color::white(),
+                    };
+
+                    c.set_fill_style_str(&color.to_string());
+                    c.begin_path();
+                    c.arc(x_mid, y_mid, cell_width.min(cell_height) * 0.35, 0.0, 2.0 * PI)
+                        .unwrap();
+                    c.fill();
+                }
                 layout::Cell::Empty => {}
                 layout::Cell::Letter(letter, color) => {
                     c.set_fill_style_str(&color.to_string());
                     c.fill_rect(x, y_high, cell_width, cell_height);
 
-                    c.set_fill_style_str(&Color::black().to_string());
-                    c.set_line_width(4.0);
+                    c.set_fill_style_str(&theme.outline.to_string());
 
-                    c.set_font("bold 2vmin sans-serif");
+                    c.set_font(&format!("bold {}px sans-serif", (cell_size * 0.6).floor()));
                     c.set_text_align("center");
                     c.set_text_baseline("middle");
 
@@ -152,4 +456,177 @@ pub fn draw_board(
             }
         }
     }
+
+    // explosions are drawn last, because they extend beyond their own cell
+    for row_i in 0..grid.height() {
+        for cell_i in 0..grid.width() {
+            if let layout::Cell::Explosion(frame, color) = grid.get(cell_i, row_i) {
+                let x_mid = offset_x + cell_i as f64 * cell_size + cell_size * 0.5;
+                let y_mid = offset_y + row_i as f64 * cell_size + cell_size * 0.5;
+                draw_explosion(frame, &color, theme, c, x_mid, y_mid, cell_size);
+            }
+        }
+    }
+}
+
+/// Draw faint lines between the cells of the arena, with the column numbers along the top and the
+/// row numbers along the left, to compare the board with the grid of the debug view.
+pub fn draw_grid_overlay(
+    c: &CanvasRenderingContext2d,
+    grid: &layout::Grid,
+    canvas: &HtmlCanvasElement,
+    theme: &Theme,
+) {
+    let (cell_size, offset_x, offset_y) = board_area(grid, canvas);
+    let width = grid.width() as f64 * cell_size;
+    let height = grid.height() as f64 * cell_size;
+
+    // all lines are stroked as one path, half a pixel off so they stay one pixel wide
+    c.set_stroke_style_str(&theme.grid.with_alpha(0.15).to_string());
+    c.set_line_width(1.0);
+    c.begin_path();
+    for column in 0..=grid.width() {
+        let x = offset_x + column as f64 * cell_size + 0.5;
+        c.move_to(x, offset_y);
+        c.line_to(x, offset_y + height);
+    }
+    for row in 0..=grid.height() {
+        let y = offset_y + row as f64 * cell_size + 0.5;
+        c.move_to(offset_x, y);
+        c.line_to(offset_x + width, y);
+    }
+    c.stroke();
+
+    // small cells only have room for every fifth number
+    let step = if cell_size >= 16.0 { 1 } else { 5 };
+    c.set_fill_style_str(&theme.grid.with_alpha(0.4).to_string());
+    c.set_font(&format!("{}px monospace", (cell_size * 0.45).max(6.0).floor()));
+    c.set_text_align("center");
+    c.set_text_baseline("middle");
+    for column in (0..grid.width()).step_by(step) {
+        let x_mid = offset_x + (column as f64 + 0.5) * cell_size;
+        let _ = c.fill_text(&column.to_string(), x_mid, offset_y + cell_size * 0.5);
+    }
+    for row in (step..grid.height()).step_by(step) {
+        let y_mid = offset_y + (row as f64 + 0.5) * cell_size;
+        let _ = c.fill_text(&row.to_string(), offset_x + cell_size * 0.5, y_mid);
+    }
+}
+
+/// Mark the places where a trail wraps around the edge of the arena, with a short line on both
+/// edges that the trail crosses, so the two ends can be told to belong together.
+pub fn draw_wraps(
+    c: &CanvasRenderingContext2d,
+    grid: &layout::Grid,
+    canvas: &HtmlCanvasElement,
+    game_state: &GameState,
+) {
+    let (cell_size, offset_x, offset_y) = board_area(grid, canvas);
+    let (width, height) = (grid.width(), grid.height());
+    let (left, top) = (offset_x, offset_y);
+    let (right, bottom) = (left + width as f64 * cell_size, top + height as f64 * cell_size);
+    let margin = cell_size * 0.15;
+
+    c.set_line_width(line_width(cell_size) * 0.5);
+    for player in &game_state.players {
+        c.set_stroke_style_str(&player.color.lighten().with_alpha(0.6).to_string());
+        c.begin_path();
+
+        for ((from, _), (to, _)) in player.segments.iter().zip(player.segments.iter().skip(1)) {
+            // neighboring segments are more than a cell apart only where the trail wraps
+            if from.x.abs_diff(to.x) > 1 {
+                let y = top + from.y as f64 * cell_size;
+                for x in [left, right] {
+                    c.move_to(x, y + margin);
+                    c.line_to(x, y + cell_size - margin);
+                }
+            } else if from.y.abs_diff(to.y) > 1 {
+                let x = left + from.x as f64 * cell_size;
+                for y in [top, bottom] {
+                    c.move_to(x + margin, y);
+                    c.line_to(x + cell_size - margin, y);
+                }
+            }
+        }
+
+        c.stroke();
+    }
+}
+
+/// Draw what the bots thought of the directions that they could go, around the cell that each
+/// bot decided in: a green dot for a safe direction, a red dot for an unsafe one, and a ring
+/// around the direction that the bot chose. The scores are written next to the dots.
+pub fn draw_bot_decisions(
+    c: &CanvasRenderingContext2d,
+    grid: &layout::Grid,
+    canvas: &HtmlCanvasElement,
+    game_state: &GameState,
+    theme: &Theme,
+) {
+    let (cell_size, offset_x, offset_y) = board_area(grid, canvas);
+    c.set_line_width(2.0);
+    c.set_font(&format!("{}px monospace", (cell_size * 0.4).max(6.0).floor()));
+    c.set_text_align("center");
+    c.set_text_baseline("top");
+
+    for decision in game_state.bot_decisions.values() {
+        for consideration in &decision.considered {
+            let Some(cell) = game_state.next_position(&decision.position, &consideration.direction)
+            else {
+                continue;
+            };
+            let x_mid = offset_x + (cell.x as f64 + 0.5) * cell_size;
+            let y_mid = offset_y + (cell.y as f64 + 0.5) * cell_size;
+
+            let color = if consideration.safe { Color::green() } else { Color::red() };
+            c.set_fill_style_str(&color.with_alpha(0.7).to_string());
+            c.begin_path();
+            let _ = c.arc(x_mid, y_mid, cell_size * 0.15, 0.0, 2.0 * PI);
+            c.fill();
+
+            if consideration.direction == decision.direction {
+                c.set_stroke_style_str(&theme.grid.with_alpha(0.8).to_string());
+                c.begin_path();
+                let _ = c.arc(x_mid, y_mid, cell_size * 0.35, 0.0, 2.0 * PI);
+                c.stroke();
+            }
+
+            if let Some(score) = consideration.score {
+                c.set_fill_style_str(&theme.grid.with_alpha(0.8).to_string());
+                let _ = c.fill_text(&score.to_string(), x_mid, y_mid + cell_size * 0.2);
+            }
+        }
+    }
+}
+
+/// Draw scanlines and a vignette over everything that was drawn before, for a retro look. All
+/// scanlines are filled as one path, so this stays cheap enough to draw on every frame.
+pub fn draw_crt(c: &CanvasRenderingContext2d, canvas: &HtmlCanvasElement) {
+    let width = canvas.width() as f64;
+    let height = canvas.height() as f64;
+    let scanline_spacing = 3.0;
+
+    c.set_fill_style_str(&Color::black().with_alpha(0.25).to_string());
+    c.begin_path();
+    let mut y = 0.0;
+    while y < height {
+        c.rect(0.0, y, width, 1.0);
+        y += scanline_spacing;
+    }
+    c.fill();
+
+    // the vignette darkens the corners, from fully clear in the middle of the screen
+    let (x_mid, y_mid) = (width * 0.5, height * 0.5);
+    let radius = width.hypot(height) * 0.5;
+    let vignette = c
+        .create_radial_gradient(x_mid, y_mid, radius * 0.6, x_mid, y_mid, radius)
+        .unwrap();
+    vignette
+        .add_color_stop(0.0, &Color::black().with_alpha(0.0).to_string())
+        .unwrap();
+    vignette
+        .add_color_stop(1.0, &Color::black().with_alpha(0.6).to_string())
+        .unwrap();
+    c.set_fill_style_canvas_gradient(&vignette);
+    c.fill_rect(0.0, 0.0, width, height);
 }
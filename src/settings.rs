@@ -0,0 +1,96 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use serde::{Deserialize, Serialize};
+
+use cordon_core::game::ArenaMode;
+
+use crate::{bindings::KeyBindings, storage, GameOptions};
+
+/// The local storage key under which the settings are kept.
+const SETTINGS_KEY: &str = "cordon.settings";
+
+/// The music volume when nothing was chosen yet, from 0.0 (off) to 1.0 (loudest).
+const DEFAULT_MUSIC_VOLUME: f64 = 0.5;
+
+/// The preferences of the player, which are remembered in local storage between visits.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// The choices in the menus, which are used to set up the next game.
+    pub options: GameOptions,
+    /// Whether the game is played fullscreen. Browsers only allow fullscreen after the user has
+    /// interacted with the page, so it is restored on the first key press or click.
+    pub fullscreen: bool,
+    /// Whether the sound effects are muted.
+    pub muted: bool,
+    /// The volume of the music, from 0.0 (off) to 1.0 (loudest).
+    pub music_volume: f64,
+    pub key_bindings: KeyBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            options: GameOptions::default(),
+            fullscreen: false,
+            muted: false,
+            music_volume: DEFAULT_MUSIC_VOLUME,
+            key_bindings: KeyBindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    /// Read the settings from local storage. Without any stored settings, the ones that were
+    /// stored separately by earlier versions are used, and the defaults for the rest.
+    pub fn load() -> Self {
+        storage::get(SETTINGS_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_else(Settings::load_separate)
+    }
+
+    /// Write the settings to local storage.
+    pub fn save(&self) {
+        let json = serde_json::to_string(self).expect("settings should be serializable");
+        storage::set(SETTINGS_KEY, &json);
+    }
+
+    /// Read the settings that earlier versions stored under keys of their own.
+    fn load_separate() -> Self {
+        let defaults = Settings::default();
+        let arena_mode = storage::get("cordon.arena_mode")
+            .and_then(|json| serde_json::from_str::<ArenaMode>(&json).ok());
+
+        Settings {
+            options: GameOptions {
+                arena_mode: arena_mode.unwrap_or(defaults.options.arena_mode),
+                ..defaults.options
+            },
+            muted: storage::get("cordon.muted").as_deref() == Some("true"),
+            music_volume: storage::get("cordon.music_volume")
+                .and_then(|volume| volume.parse().ok())
+                .unwrap_or(defaults.music_volume),
+            key_bindings: storage::get("cordon.key_bindings")
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or(defaults.key_bindings),
+            ..defaults
+        }
+    }
+}
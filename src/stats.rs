@@ -0,0 +1,143 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use serde::{Deserialize, Serialize};
+
+use cordon_core::{
+    bot::BotDifficulty,
+    common::Color,
+    game::{Controller, GameState},
+};
+
+use crate::storage;
+
+/// The local storage key under which the statistics are kept.
+const STATS_KEY: &str = "cordon.stats";
+
+/// The local storage key under which the high scores of survival games are kept.
+const HIGH_SCORES_KEY: &str = "cordon.high_scores";
+
+/// The number of high scores that are kept.
+const MAX_HIGH_SCORES: usize = 10;
+
+/// Statistics over all games played in this browser.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Stats {
+    pub games_played: u32,
+    pub draws: u32,
+    /// The number of wins of each player color.
+    pub wins_by_color: Vec<(Color, u32)>,
+    /// The number of wins of each controller, like "Arrows" or "Bot (Hard)".
+    pub wins_by_controller: Vec<(String, u32)>,
+    /// The most segments that a single trail has had.
+    pub longest_trail: usize,
+}
+
+impl Stats {
+    /// Read the statistics from local storage. Without any stored statistics, everything is zero.
+    pub fn load() -> Self {
+        storage::get(STATS_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the statistics to local storage.
+    pub fn save(&self) {
+        let json = serde_json::to_string(self).expect("stats should be serializable");
+        storage::set(STATS_KEY, &json);
+    }
+
+    /// Count a finished game.
+    pub fn record(&mut self, game_state: &GameState) {
+        self.games_played += 1;
+        self.longest_trail = self.longest_trail.max(game_state.longest_trail);
+
+        match game_state.winner() {
+            Some(winner) => {
+                let player = &game_state.players[winner];
+                increment(&mut self.wins_by_color, player.color);
+                increment(&mut self.wins_by_controller, player.controller.to_string());
+            }
+            None => self.draws += 1,
+        }
+    }
+}
+
+/// A finished survival game that made it into the high scores.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct HighScore {
+    /// The number of rounds that the human survived.
+    pub rounds: u32,
+    /// The difficulty of the bots that the human faced.
+    pub bot_difficulty: BotDifficulty,
+}
+
+/// The best survival games played in this browser, from best to worst.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    pub entries: Vec<HighScore>,
+}
+
+impl HighScores {
+    /// Read the high scores from local storage. Without any stored high scores, the table is empty.
+    pub fn load() -> Self {
+        storage::get(HIGH_SCORES_KEY)
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Write the high scores to local storage.
+    pub fn save(&self) {
+        let json = serde_json::to_string(self).expect("high scores should be serializable");
+        storage::set(HIGH_SCORES_KEY, &json);
+    }
+
+    /// Enter a finished survival game, if it is good enough. Returns its place in the table, if it
+    /// made it in.
+    pub fn record(&mut self, game_state: &GameState) -> Option<usize> {
+        let human = game_state
+            .players
+            .iter()
+            .find(|p| !matches!(p.controller, Controller::Bot(_)))?;
+        let bot_difficulty = game_state.players.iter().find_map(|p| match p.controller {
+            Controller::Bot(difficulty) => Some(difficulty),
+            _ => None,
+        })?;
+
+        // a later game needs a better score to pass an earlier one
+        let place = self.entries.iter().position(|e| e.rounds < human.score);
+        let place = place.unwrap_or(self.entries.len());
+        if place >= MAX_HIGH_SCORES {
+            return None;
+        }
+
+        let rounds = human.score;
+        self.entries.insert(place, HighScore { rounds, bot_difficulty });
+        self.entries.truncate(MAX_HIGH_SCORES);
+        Some(place)
+    }
+}
+
+/// Add one to the count of the specified key, starting a new count if needed.
+fn increment<K: PartialEq>(counts: &mut Vec<(K, u32)>, key: K) {
+    match counts.iter_mut().find(|(k, _)| *k == key) {
+        Some((_, count)) => *count += 1,
+        None => counts.push((key, 1)),
+    }
+}
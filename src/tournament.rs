@@ -0,0 +1,108 @@
+// Cordon
+//
+// Copyright 2025 Remco Kranenburg <remco@burgsoft.nl>
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU Affero General Public License as
+// published by the Free Software Foundation, either version 3 of the
+// License, or (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU Affero General Public License for more details.
+//
+// You should have received a copy of the GNU Affero General Public License
+// along with this program.  If not, see <https://www.gnu.org/licenses/>.
+//
+// SPDX-License-Identifier: AGPL-3.0-or-later
+
+use cordon_core::game::{Controller, GameState};
+
+/// A series of matches between two players, in which every player of the tournament plays every
+/// other player once. The points of each match are added up over the whole tournament.
+#[derive(Clone, Debug)]
+pub struct Tournament {
+    /// The controller of every player in the tournament.
+    pub entrants: Vec<Controller>,
+    /// The pairs of players that play each other, in the order that they play.
+    pub schedule: Vec<(usize, usize)>,
+    /// The index in the schedule of the match that is played now, or that is played next.
+    pub current_match: usize,
+    /// The number of matches that each player has won.
+    pub wins: Vec<u32>,
+    /// The number of points that each player has scored over all matches.
+    pub points: Vec<u32>,
+}
+
+impl Tournament {
+    pub fn new(entrants: Vec<Controller>) -> Self {
+        let num_entrants = entrants.len();
+
+        Tournament {
+            entrants,
+            schedule: schedule(num_entrants),
+            current_match: 0,
+            wins: vec![0; num_entrants],
+            points: vec![0; num_entrants],
+        }
+    }
+
+    /// The players of the match that is played now, or `None` once the tournament is over.
+    pub fn current_pair(&self) -> Option<(usize, usize)> {
+        self.schedule.get(self.current_match).copied()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current_match >= self.schedule.len()
+    }
+
+    /// Add the result of the finished game, which was the current match, and move on to the next.
+    pub fn record(&mut self, game_state: &GameState) {
+        let Some((first, second)) = self.current_pair() else {
+            return;
+        };
+
+        for (entrant, player) in [first, second].into_iter().zip(&game_state.players) {
+            self.points[entrant] += player.score;
+        }
+
+        // a drawn match is not won by anyone
+        if let Some(winner) = game_state.winner() {
+            self.wins[[first, second][winner]] += 1;
+        }
+
+        self.current_match += 1;
+    }
+
+    /// All players from first to last place: by matches won, and then by points.
+    pub fn standings(&self) -> Vec<usize> {
+        let mut standings: Vec<usize> = (0..self.entrants.len()).collect();
+        standings.sort_by_key(|i| std::cmp::Reverse((self.wins[*i], self.points[*i])));
+        standings
+    }
+}
+
+/// Pair up everyone with everyone else, in rounds in which nobody plays more than once. One
+/// player stays in place while the others rotate around it, and with an odd number of players,
+/// someone sits out every round.
+fn schedule(num_entrants: usize) -> Vec<(usize, usize)> {
+    let mut seats: Vec<Option<usize>> = (0..num_entrants).map(Some).collect();
+    if num_entrants % 2 == 1 {
+        seats.push(None);
+    }
+
+    let num_seats = seats.len();
+    let mut schedule = Vec::new();
+
+    for _ in 1..num_seats {
+        for i in 0..num_seats / 2 {
+            if let (Some(a), Some(b)) = (seats[i], seats[num_seats - 1 - i]) {
+                schedule.push((a.min(b), a.max(b)));
+            }
+        }
+        seats[1..].rotate_right(1);
+    }
+
+    schedule
+}